@@ -0,0 +1,239 @@
+use std::collections::HashMap;
+
+use crate::geohash::{Area, Coord, GeoBits};
+
+/// Encodes each coordinate at `precision` and counts how many fall in each
+/// cell.
+pub fn histogram<I: Iterator<Item = Coord>>(iter: I, precision: u8) -> HashMap<GeoBits, u32> {
+    let mut counts = HashMap::new();
+    for coord in iter {
+        *counts.entry(GeoBits::from(&coord, precision)).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Encodes each coordinate at `precision` and sums the weights falling in
+/// each cell.
+pub fn weighted_histogram<I: Iterator<Item = (Coord, f32)>>(
+    iter: I,
+    precision: u8,
+) -> HashMap<GeoBits, f32> {
+    let mut sums = HashMap::new();
+    for (coord, weight) in iter {
+        *sums.entry(GeoBits::from(&coord, precision)).or_insert(0f32) += weight;
+    }
+    sums
+}
+
+/// Encodes each point of `track` and collapses consecutive repeats, giving
+/// the cells the track passes through in order. A cell left and entered
+/// again appears again.
+pub fn trajectory_cells(track: &[Coord], precision: u8) -> Vec<GeoBits> {
+    let mut cells: Vec<GeoBits> = track
+        .iter()
+        .map(|coord| GeoBits::from(coord, precision))
+        .collect();
+    cells.dedup();
+    cells
+}
+
+/// Returns the cell with the highest count, breaking ties in favor of the
+/// cell first in Morton order so the result does not depend on hash order.
+pub fn densest_cell(hist: &HashMap<GeoBits, u32>) -> Option<(&GeoBits, u32)> {
+    hist.iter()
+        .min_by_key(|(cell, count)| {
+            (
+                std::cmp::Reverse(**count),
+                cell.morton_code(),
+                cell.precision(),
+            )
+        })
+        .map(|(cell, count)| (cell, *count))
+}
+
+/// Counts the coordinates per cell at every precision from `min_p` to `max_p`,
+/// coarsest first. Each point is encoded once at `max_p` and the counts are
+/// rolled up through `parent()`, which is much cheaper than re-encoding.
+pub fn pyramid<I: Iterator<Item = Coord>>(
+    iter: I,
+    min_p: u8,
+    max_p: u8,
+) -> Vec<HashMap<GeoBits, u32>> {
+    if min_p == 0 || min_p > max_p {
+        panic!("Precisions should satisfy 1 <= min_p <= max_p");
+    }
+    let mut levels = vec![histogram(iter, max_p)];
+    for _ in min_p..max_p {
+        let mut counts = HashMap::new();
+        for (cell, count) in levels.last().unwrap() {
+            *counts.entry(cell.parent().unwrap()).or_insert(0) += count;
+        }
+        levels.push(counts);
+    }
+    levels.reverse();
+    levels
+}
+
+/// Returns the spherical mean of `coords`: the average of their unit vectors
+/// projected back onto the sphere. Unlike averaging latitudes and longitudes,
+/// this works across the antimeridian. Returns `None` for no coordinates or
+/// when the points cancel out, e.g. two antipodes.
+pub fn centroid(coords: &[Coord]) -> Option<Coord> {
+    let mut sum = [0f64; 3];
+    for coord in coords {
+        let v = coord.to_unit_vector();
+        sum = [sum[0] + v[0], sum[1] + v[1], sum[2] + v[2]];
+    }
+    let norm = (sum[0] * sum[0] + sum[1] * sum[1] + sum[2] * sum[2]).sqrt();
+    if norm < 1e-9 {
+        return None;
+    }
+    Some(Coord::from_unit_vector(sum))
+}
+
+/// Returns a center and a radius in meters for a circle containing the center
+/// of every cell. This is an approximation rather than the smallest such
+/// circle: the center is the spherical mean of the cell centers and the radius
+/// is the distance to the farthest of them, which can be up to twice the
+/// minimal radius for lopsided sets. Returns `None` for no cells.
+pub fn enclosing_circle(cells: &[GeoBits]) -> Option<(Coord, f32)> {
+    let centers: Vec<Coord> = cells
+        .iter()
+        .map(|cell| Area::from(*cell).center())
+        .collect();
+    let first = *centers.first()?;
+    // Centers that cancel out have no meaningful mean; any of them will do.
+    let center = centroid(&centers).unwrap_or(first);
+    let radius = centers
+        .iter()
+        .map(|coord| center.haversine(coord))
+        .fold(0f32, f32::max);
+    Some((center, radius))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn histogram() {
+        let coords = vec![
+            Coord::new(25.006, 121.46),
+            Coord::new(25.007, 121.461),
+            Coord::new(25.005, 121.459),
+            Coord::new(-33.87, 151.21),
+        ];
+        let counts = super::histogram(coords.into_iter(), 10);
+        assert_eq!(counts.len(), 2);
+        assert_eq!(counts[&GeoBits::from(&Coord::new(25.006, 121.46), 10)], 3);
+        assert_eq!(counts[&GeoBits::from(&Coord::new(-33.87, 151.21), 10)], 1);
+    }
+
+    #[test]
+    fn weighted_histogram() {
+        let points = vec![
+            (Coord::new(25.006, 121.46), 1.5),
+            (Coord::new(25.007, 121.461), 2.0),
+            (Coord::new(-33.87, 151.21), 4.25),
+        ];
+        let sums = super::weighted_histogram(points.into_iter(), 10);
+        assert_eq!(sums.len(), 2);
+        assert_eq!(sums[&GeoBits::from(&Coord::new(25.006, 121.46), 10)], 3.5);
+        assert_eq!(sums[&GeoBits::from(&Coord::new(-33.87, 151.21), 10)], 4.25);
+    }
+
+    #[test]
+    fn trajectory_cells() {
+        let home = Coord::new(25.006, 121.46);
+        let away = Coord::new(25.2, 121.46);
+        let track = [
+            home,
+            Coord::new(25.0061, 121.4601),
+            Coord::new(25.0062, 121.4602),
+            away,
+            away,
+            home,
+        ];
+        let cells = super::trajectory_cells(&track, 15);
+        let (home, away) = (GeoBits::from(&home, 15), GeoBits::from(&away, 15));
+        assert_eq!(cells, vec![home, away, home]);
+        assert!(super::trajectory_cells(&[], 15).is_empty());
+    }
+
+    #[test]
+    fn densest_cell() {
+        let coords = vec![
+            Coord::new(25.006, 121.46),
+            Coord::new(25.007, 121.461),
+            Coord::new(-33.87, 151.21),
+        ];
+        let counts = super::histogram(coords.into_iter(), 10);
+        let winner = GeoBits::from(&Coord::new(25.006, 121.46), 10);
+        assert_eq!(super::densest_cell(&counts), Some((&winner, 2)));
+
+        let tied = super::histogram(
+            [Coord::new(25.006, 121.46), Coord::new(-33.87, 151.21)].into_iter(),
+            10,
+        );
+        let first = tied.keys().min_by_key(|cell| cell.morton_code()).unwrap();
+        assert_eq!(super::densest_cell(&tied), Some((first, 1)));
+        assert_eq!(super::densest_cell(&HashMap::new()), None);
+    }
+
+    #[test]
+    fn pyramid() {
+        let coords = vec![
+            Coord::new(25.006, 121.46),
+            Coord::new(25.007, 121.461),
+            Coord::new(25.5, 121.9),
+            Coord::new(-33.87, 151.21),
+        ];
+        let levels = super::pyramid(coords.clone().into_iter(), 4, 12);
+        assert_eq!(levels.len(), 9);
+        assert_eq!(levels[8], super::histogram(coords.into_iter(), 12));
+        for pair in levels.windows(2) {
+            for (cell, count) in &pair[0] {
+                let children: u32 = cell.children().iter().filter_map(|c| pair[1].get(c)).sum();
+                assert_eq!(*count, children);
+            }
+            assert_eq!(pair[0].values().sum::<u32>(), 4);
+        }
+    }
+
+    #[test]
+    fn enclosing_circle() {
+        let cells: Vec<GeoBits> = [(25.0, 121.0), (25.5, 121.9), (24.2, 120.4), (25.1, 179.9)]
+            .iter()
+            .map(|(lat, lng)| GeoBits::from(&Coord::new(*lat, *lng), 20))
+            .collect();
+        let (center, radius) = super::enclosing_circle(&cells).unwrap();
+        for cell in &cells {
+            assert!(center.haversine(&Area::from(*cell).center()) <= radius);
+        }
+
+        let cell = GeoBits::from(&Coord::new(25.006, 121.46), 20);
+        let (center, radius) = super::enclosing_circle(&[cell]).unwrap();
+        assert!(cell.covers(&center));
+        assert!(radius < 1.0);
+
+        assert_eq!(super::enclosing_circle(&[]), None);
+    }
+
+    #[test]
+    fn centroid() {
+        let coords = [
+            Coord::new(10.0, 179.0),
+            Coord::new(-10.0, -179.0),
+            Coord::new(0.0, 179.5),
+        ];
+        let centroid = super::centroid(&coords).unwrap();
+        assert!(centroid.longitude.abs() > 179.0);
+        assert!(centroid.latitude.abs() < 1.0);
+
+        assert_eq!(super::centroid(&[]), None);
+        assert_eq!(
+            super::centroid(&[Coord::new(0.0, 0.0), Coord::new(0.0, -180.0)]),
+            None
+        );
+    }
+}
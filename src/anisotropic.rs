@@ -0,0 +1,117 @@
+use std::ops::Range;
+
+use crate::geohash::{index_range, Area, Coord};
+
+/// A cell with separate latitude and longitude precisions, for elongated
+/// cells. Bits alternate longitude/latitude like a regular geohash until the
+/// shorter axis runs out, then the rest of the longer axis follows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AnisotropicBits {
+    bits: u64,
+    lat_bits: u8,
+    lng_bits: u8,
+}
+
+pub fn encode_anisotropic(coord: &Coord, lat_bits: u8, lng_bits: u8) -> AnisotropicBits {
+    if !(1..=32).contains(&lat_bits) || !(1..=32).contains(&lng_bits) {
+        panic!("Bit counts should satisfy 1 <= bits <= 32");
+    }
+    let lat = quantize(coord.latitude, &(-90f32..90f32), lat_bits);
+    let lng = quantize(coord.longitude, &(-180f32..180f32), lng_bits);
+
+    let (mut lat_left, mut lng_left) = (lat_bits, lng_bits);
+    let mut bits = 0u64;
+    while lat_left + lng_left > 0 {
+        let bit = if next_is_lng(lat_bits - lat_left, lng_bits - lng_left, lat_left, lng_left) {
+            lng_left -= 1;
+            (lng >> lng_left) & 1
+        } else {
+            lat_left -= 1;
+            (lat >> lat_left) & 1
+        };
+        bits = (bits << 1) | bit;
+    }
+    AnisotropicBits {
+        bits,
+        lat_bits,
+        lng_bits,
+    }
+}
+
+pub fn decode_anisotropic(hash: &AnisotropicBits) -> Area {
+    let (mut lat_left, mut lng_left) = (hash.lat_bits, hash.lng_bits);
+    let (mut lat, mut lng) = (0u64, 0u64);
+    let mut remaining = hash.lat_bits + hash.lng_bits;
+    while remaining > 0 {
+        remaining -= 1;
+        let bit = (hash.bits >> remaining) & 1;
+        let lat_done = hash.lat_bits - lat_left;
+        let lng_done = hash.lng_bits - lng_left;
+        if next_is_lng(lat_done, lng_done, lat_left, lng_left) {
+            lng_left -= 1;
+            lng = (lng << 1) | bit;
+        } else {
+            lat_left -= 1;
+            lat = (lat << 1) | bit;
+        }
+    }
+    Area {
+        lat_range: index_range(lat as u32, hash.lat_bits, &(-90f32..90f32)),
+        lng_range: index_range(lng as u32, hash.lng_bits, &(-180f32..180f32)),
+    }
+}
+
+// Longitude goes first, as in a regular geohash, and the axes alternate until
+// one runs out.
+fn next_is_lng(lat_done: u8, lng_done: u8, lat_left: u8, lng_left: u8) -> bool {
+    lng_left > 0 && (lat_left == 0 || lng_done <= lat_done)
+}
+
+fn quantize(value: f32, range: &Range<f32>, bits: u8) -> u64 {
+    let length = (range.end - range.start) as f64;
+    ((value as f64 - range.start as f64) * ((1u64 << bits) as f64) / length) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bits::tests::next as next_random;
+    use crate::geohash::{GeoBits, RangeExtension};
+
+    use super::*;
+
+    #[test]
+    fn anisotropic() {
+        let coord = Coord::new(25.006, 121.46);
+        let hash = encode_anisotropic(&coord, 10, 14);
+        let area = decode_anisotropic(&hash);
+        assert!(area.contains(&coord));
+        // Four more longitude bits make the cell 16 times finer east-west, so
+        // it is 8 times narrower than it is tall in degrees.
+        assert_eq!(area.lat_range.length() / area.lng_range.length(), 8.0);
+
+        // Equal bit counts are a regular geohash.
+        let hash = encode_anisotropic(&coord, 15, 15);
+        assert_eq!(hash.bits, GeoBits::from(&coord, 15).bits);
+        assert_eq!(decode_anisotropic(&hash), GeoBits::from(&coord, 15).into());
+
+        // The extra longitude bits come last.
+        let hash = encode_anisotropic(&coord, 1, 3);
+        let lng = ((121.46f32 + 180.0) / 360.0 * 8.0) as u64;
+        let lat = 1;
+        assert_eq!(hash.bits, (lng >> 2) << 3 | lat << 2 | (lng & 0b11));
+    }
+
+    #[test]
+    fn anisotropic_contains_coord() {
+        let mut state = 5;
+        for bits in [(20, 20), (24, 24), (32, 32), (12, 32), (32, 9)] {
+            for _ in 0..2000 {
+                let lat = (next_random(&mut state) % 180_000_000) as f32 / 1e6 - 90.0;
+                let lng = (next_random(&mut state) % 360_000_000) as f32 / 1e6 - 180.0;
+                let coord = Coord::new(lat, lng);
+                let area = decode_anisotropic(&encode_anisotropic(&coord, bits.0, bits.1));
+                assert!(area.contains(&coord), "{:?} at {:?}", coord, bits);
+            }
+        }
+    }
+}
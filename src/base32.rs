@@ -0,0 +1,310 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::GeoError;
+use crate::geohash::{Area, Coord, GeoBits, IntoGeoBits};
+
+const ALPHABET: &[u8; 32] = b"0123456789bcdefghjkmnpqrstuvwxyz";
+// 13 characters carry 65 bits, enough for precision 32; the padding is done
+// in a u128 since that does not fit in a u64.
+const MAX_LEN: usize = 13;
+
+impl GeoBits {
+    /// Returns the standard geohash string. Each character holds 5 bits, so
+    /// when `precision * 2` is not a multiple of 5 the last character is
+    /// padded with zero bits and denotes the south-west-most descendant of
+    /// this cell.
+    pub fn to_base32(&self) -> String {
+        let num_bits = self.precision as u32 * 2;
+        let num_chars = num_bits.div_ceil(5);
+        let padded = (self.bits as u128) << (num_chars * 5 - num_bits);
+        (0..num_chars)
+            .rev()
+            .map(|i| ALPHABET[((padded >> (i * 5)) & 0x1F) as usize] as char)
+            .collect()
+    }
+
+    /// Returns `to_base32` with `sep` inserted after every `group` characters,
+    /// e.g. `"u4pr-uyd"`. A `group` of 0 inserts nothing.
+    pub fn to_base32_grouped(&self, group: usize, sep: char) -> String {
+        let plain = self.to_base32();
+        if group == 0 {
+            return plain;
+        }
+        let mut grouped = String::with_capacity(plain.len() * 2);
+        for (i, c) in plain.chars().enumerate() {
+            if i > 0 && i % group == 0 {
+                grouped.push(sep);
+            }
+            grouped.push(c);
+        }
+        grouped
+    }
+
+    /// Decodes a standard geohash string. A string of `n` characters carries
+    /// `5n` bits; when that is odd, the trailing longitude bit is dropped since
+    /// `GeoBits` needs as many latitude bits as longitude bits.
+    pub fn from_base32(s: &str) -> Result<GeoBits, GeoError> {
+        let precision = base32_precision(s)?;
+        let mut bits = 0u128;
+        for c in s.chars() {
+            bits = (bits << 5) | decode_char(c)? as u128;
+        }
+        Ok(GeoBits {
+            bits: (bits >> (s.len() * 5 - precision as usize * 2)) as u64,
+            precision,
+        })
+    }
+
+    /// Decodes a geohash string typed or copied by a person: uppercase is
+    /// accepted, and the letters `o`, `i` and `l`, which are not in the
+    /// alphabet, are read as the digits they are usually mistaken for.
+    pub fn from_base32_lenient(s: &str) -> Result<GeoBits, GeoError> {
+        let normalized: String = s
+            .chars()
+            .map(|c| match c.to_ascii_lowercase() {
+                'o' => '0',
+                'i' | 'l' => '1',
+                c => c,
+            })
+            .collect();
+        GeoBits::from_base32(&normalized)
+    }
+}
+
+/// A `GeoBits` that formats and parses as its base32 string, e.g. for
+/// storing `"u4pruyd"` in documents instead of the bits and precision.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Base32(pub GeoBits);
+
+impl fmt::Display for Base32 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0.to_base32())
+    }
+}
+
+impl FromStr for Base32 {
+    type Err = GeoError;
+
+    fn from_str(s: &str) -> Result<Base32, GeoError> {
+        GeoBits::from_base32(s).map(Base32)
+    }
+}
+
+impl IntoGeoBits for &str {
+    /// Decodes the geohash string and returns its ancestor at `precision`,
+    /// which must not be finer than the string itself.
+    fn into_geobits(self, precision: u8) -> Result<GeoBits, GeoError> {
+        let hash = GeoBits::from_base32(self)?;
+        if precision == 0 || precision > hash.precision {
+            return Err(GeoError::InvalidPrecision(precision));
+        }
+        Ok(GeoBits {
+            bits: hash.bits >> ((hash.precision - precision) as u32 * 2),
+            precision,
+        })
+    }
+}
+
+/// Decodes a geohash string straight to the center of its cell.
+pub fn decode_base32(s: &str) -> Result<Coord, GeoError> {
+    Ok(Area::from(GeoBits::from_base32(s)?).center())
+}
+
+/// Encodes `coord` at the finest precision whose `to_base32` string is `chars`
+/// characters long.
+pub fn encode_chars(coord: &Coord, chars: u8) -> Result<GeoBits, GeoError> {
+    if chars == 0 || chars as usize > MAX_LEN {
+        return Err(GeoError::InvalidLength(chars as usize));
+    }
+    Ok(GeoBits::from(coord, chars * 5 / 2))
+}
+
+/// Validates `s` as a geohash string and returns the precision it decodes to,
+/// without decoding it.
+pub fn base32_precision(s: &str) -> Result<u8, GeoError> {
+    if let Some(c) = s
+        .chars()
+        .find(|c| !c.is_ascii() || !ALPHABET.contains(&(*c as u8)))
+    {
+        return Err(GeoError::InvalidCharacter(c));
+    }
+    if s.is_empty() || s.len() > MAX_LEN {
+        return Err(GeoError::InvalidLength(s.len()));
+    }
+    Ok((s.len() * 5 / 2) as u8)
+}
+
+fn decode_char(c: char) -> Result<u64, GeoError> {
+    ALPHABET
+        .iter()
+        .position(|a| c.is_ascii() && *a == c as u8)
+        .map(|i| i as u64)
+        .ok_or(GeoError::InvalidCharacter(c))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geohash::Coord;
+
+    use super::*;
+
+    #[test]
+    fn base32() {
+        let hash = GeoBits::from(&Coord::new(57.64911, 10.40744), 15);
+        assert_eq!(hash.to_base32(), "u4pruy");
+        assert_eq!(GeoBits::from_base32("u4pruy"), Ok(hash));
+
+        // Odd bit counts drop the trailing longitude bit.
+        let hash = GeoBits::from_base32("u4pruydqqvj").unwrap();
+        assert_eq!(hash.precision, 27);
+        assert_eq!(hash.to_base32(), "u4pruydqqvh");
+    }
+
+    #[test]
+    fn base32_finest_precisions() {
+        let coord = Coord::new(57.64911, 10.40744);
+        let prefix = GeoBits::from(&coord, 30).to_base32();
+        assert_eq!(prefix, "u4pruydqquvc");
+        for precision in [31, 32] {
+            let hash = GeoBits::from(&coord, precision);
+            let text = hash.to_base32();
+            assert_eq!(text.len(), 13);
+            assert!(text.starts_with(&prefix));
+            assert_eq!(hash.to_base32_grouped(4, '-').replace('-', ""), text);
+        }
+
+        let hash = GeoBits::from(&coord, 32);
+        let text = hash.to_base32();
+        assert_eq!(GeoBits::from_base32(&text), Ok(hash));
+        assert_eq!(text.parse::<Base32>(), Ok(Base32(hash)));
+        assert_eq!(Base32(hash).to_string(), text);
+        // 13 characters always decode to precision 32, so a precision 31 cell
+        // comes back as its south-west-most child, like other padded strings.
+        let hash = GeoBits::from(&coord, 31);
+        let decoded = GeoBits::from_base32(&hash.to_base32()).unwrap();
+        assert_eq!(decoded.parent(), Some(hash));
+        assert_eq!(decoded, hash.children()[0]);
+        assert_eq!(
+            GeoBits {
+                bits: u64::MAX,
+                precision: 32
+            }
+            .to_base32(),
+            "zzzzzzzzzzzzy"
+        );
+    }
+
+    #[test]
+    fn base32_lenient() {
+        let hash = GeoBits::from_base32("u4pruy").unwrap();
+        assert_eq!(GeoBits::from_base32_lenient("U4PRUY"), Ok(hash));
+        assert_eq!(
+            GeoBits::from_base32("U4PRUY"),
+            Err(GeoError::InvalidCharacter('U'))
+        );
+
+        let hash = GeoBits::from_base32("s0d1").unwrap();
+        assert_eq!(GeoBits::from_base32_lenient("sOdl"), Ok(hash));
+        assert_eq!(GeoBits::from_base32_lenient("SoDI"), Ok(hash));
+        assert_eq!(
+            GeoBits::from_base32_lenient("s0a"),
+            Err(GeoError::InvalidCharacter('a'))
+        );
+    }
+
+    #[test]
+    fn into_geobits() {
+        let coord = Coord::new(57.64911, 10.40744);
+        let expected = GeoBits::from(&coord, 15);
+        assert_eq!(coord.into_geobits(15), Ok(expected));
+        assert_eq!((57.64911, 10.40744).into_geobits(15), Ok(expected));
+        assert_eq!("u4pruy".into_geobits(15), Ok(expected));
+        assert_eq!("u4pruydqqvj".into_geobits(15), Ok(expected));
+
+        assert_eq!(coord.into_geobits(0), Err(GeoError::InvalidPrecision(0)));
+        assert_eq!(
+            (91.0, 0.0).into_geobits(15),
+            Err(GeoError::InvalidLatitude(91.0))
+        );
+        assert_eq!(
+            "u4pruy".into_geobits(16),
+            Err(GeoError::InvalidPrecision(16))
+        );
+        assert_eq!("u4pa".into_geobits(5), Err(GeoError::InvalidCharacter('a')));
+    }
+
+    #[test]
+    fn base32_newtype() {
+        let hash = GeoBits::from_base32("u4pruyd").unwrap();
+        let text = Base32(hash).to_string();
+        assert_eq!(text, "u4pruyd");
+        assert_eq!(text.parse::<Base32>(), Ok(Base32(hash)));
+        assert_eq!(
+            "u4pa".parse::<Base32>(),
+            Err(GeoError::InvalidCharacter('a'))
+        );
+    }
+
+    #[test]
+    fn base32_grouped() {
+        let hash = GeoBits::from_base32("u4pruydqqvjq").unwrap();
+        assert_eq!(hash.to_base32_grouped(4, '-'), "u4pr-uydq-qvjq");
+        let hash = GeoBits::from(&Coord::new(57.64911, 10.40744), 17);
+        let grouped = hash.to_base32_grouped(4, '-');
+        assert_eq!(grouped, "u4pr-uyd");
+        assert_eq!(grouped.replace('-', ""), hash.to_base32());
+        assert_eq!(hash.to_base32_grouped(0, '-'), hash.to_base32());
+    }
+
+    #[test]
+    fn decode_base32() {
+        let coord = super::decode_base32("u4pruydqqvj").unwrap();
+        assert!((coord.latitude - 57.64911).abs() < 1e-4);
+        assert!((coord.longitude - 10.40744).abs() < 1e-4);
+        assert_eq!(
+            super::decode_base32("u4pa"),
+            Err(GeoError::InvalidCharacter('a'))
+        );
+    }
+
+    #[test]
+    fn encode_chars() {
+        let coord = Coord::new(57.64911, 10.40744);
+        let hash = super::encode_chars(&coord, 6).unwrap();
+        assert_eq!(hash.precision, 15);
+        assert_eq!(hash.to_base32(), "u4pruy");
+        for chars in 1..=13 {
+            let hash = super::encode_chars(&coord, chars).unwrap();
+            assert_eq!(hash.to_base32().len(), chars as usize);
+        }
+        assert_eq!(super::encode_chars(&coord, 13).unwrap().precision, 32);
+        assert_eq!(
+            super::encode_chars(&coord, 14),
+            Err(GeoError::InvalidLength(14))
+        );
+    }
+
+    #[test]
+    fn base32_precision() {
+        assert_eq!(super::base32_precision("u"), Ok(2));
+        assert_eq!(super::base32_precision("u4"), Ok(5));
+        assert_eq!(super::base32_precision("u4pruy"), Ok(15));
+        assert_eq!(super::base32_precision("u4pruydqqvj"), Ok(27));
+        assert_eq!(super::base32_precision("u4pruydqqvjq"), Ok(30));
+        assert_eq!(super::base32_precision("u4pruydqqvjqq"), Ok(32));
+        assert_eq!(
+            super::base32_precision("u4pruydqqvjqqq"),
+            Err(GeoError::InvalidLength(14))
+        );
+        assert_eq!(super::base32_precision(""), Err(GeoError::InvalidLength(0)));
+        assert_eq!(
+            super::base32_precision("u4pa"),
+            Err(GeoError::InvalidCharacter('a'))
+        );
+        assert_eq!(
+            super::base32_precision("u4pé"),
+            Err(GeoError::InvalidCharacter('é'))
+        );
+    }
+}
@@ -10,7 +10,9 @@ fn spread(x: u32) -> u64 {
 }
 
 pub fn interleave64(lat: u32, lng: u32) -> u64 {
-    (spread(lng) << 1) | spread(lat)
+    let hash = (spread(lng) << 1) | spread(lat);
+    debug_assert_eq!(deinterleave64(hash), (lng, lat));
+    hash
 }
 
 fn squash(mut x: u64) -> u32 {
@@ -26,3 +28,40 @@ fn squash(mut x: u64) -> u32 {
 pub fn deinterleave64(hash: u64) -> (u32, u32) {
     (squash(hash >> 1), squash(hash)) // (lng, lat)
 }
+
+pub fn deinterleave_lat(hash: u64) -> u32 {
+    squash(hash)
+}
+
+pub fn deinterleave_lng(hash: u64) -> u32 {
+    squash(hash >> 1)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    // SplitMix64, so that failures are reproducible from the seed.
+    pub(crate) fn next(state: &mut u64) -> u64 {
+        *state = state.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = *state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    #[test]
+    fn interleave_round_trip() {
+        let mut state = 0x5EED;
+        for _ in 0..10_000 {
+            let precision = (next(&mut state) % 32 + 1) as u32;
+            let mask = u32::MAX >> (32 - precision);
+            let lat = next(&mut state) as u32 & mask;
+            let lng = next(&mut state) as u32 & mask;
+            let hash = interleave64(lat, lng);
+            assert!(hash >> (precision * 2 - 1) <= 1);
+            assert_eq!(deinterleave64(hash), (lng, lat));
+        }
+        assert_eq!(deinterleave64(interleave64(u32::MAX, 0)), (0, u32::MAX));
+    }
+}
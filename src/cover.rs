@@ -0,0 +1,871 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::bits::{deinterleave64, interleave64};
+use crate::error::GeoError;
+use crate::geohash::{Area, Coord, GeoBits, EARTH_RADIUS_M};
+
+/// Lazily yields every cell at `precision`, all `4^precision` of them, in
+/// Morton order.
+pub fn all_cells(precision: u8) -> impl Iterator<Item = GeoBits> {
+    if precision == 0 || precision > 32 {
+        panic!("Precision should satisfy 1 <= precision <= 32");
+    }
+    let last = u64::MAX >> (64 - precision as u32 * 2);
+    (0..=last).map(move |bits| GeoBits { bits, precision })
+}
+
+/// Returns every cell at `precision` that intersects the box spanned by the
+/// south-west and north-east corners, row by row from the south-west. If `ne`
+/// lies west of `sw` the box is taken to cross the antimeridian.
+pub fn cover_bbox(sw: &Coord, ne: &Coord, precision: u8) -> Vec<GeoBits> {
+    let (lng_min, lat_min) = deinterleave64(GeoBits::from(sw, precision).bits);
+    let (lng_max, lat_max) = deinterleave64(GeoBits::from(ne, precision).bits);
+    let size = 1u64 << precision;
+    let columns = (lng_max as u64 + size - lng_min as u64) % size + 1;
+
+    let mut cells = Vec::new();
+    for lat in lat_min..=lat_max {
+        for offset in 0..columns {
+            let lng = ((lng_min as u64 + offset) % size) as u32;
+            cells.push(GeoBits {
+                bits: interleave64(lat, lng),
+                precision,
+            });
+        }
+    }
+    cells
+}
+
+/// Covers a GeoJSON `bbox` of `[west, south, east, north]`. A box whose east
+/// edge lies west of its west edge crosses the antimeridian. Edges on 90° or
+/// 180° are accepted, as GeoJSON allows them.
+pub fn cover_geojson_bbox(bbox: [f64; 4], precision: u8) -> Result<Vec<GeoBits>, GeoError> {
+    if precision == 0 || precision > 32 {
+        return Err(GeoError::InvalidPrecision(precision));
+    }
+    let [west, south, east, north] = bbox.map(|v| v as f32);
+    for lng in [west, east] {
+        if !(-180f32..=180f32).contains(&lng) {
+            return Err(GeoError::InvalidLongitude(lng));
+        }
+    }
+    for lat in [south, north] {
+        if !(-90f32..=90f32).contains(&lat) {
+            return Err(GeoError::InvalidLatitude(lat));
+        }
+    }
+    if south > north {
+        return Err(GeoError::InvalidLatitude(south));
+    }
+    // The valid ranges are half-open, so pull closed edges just inside.
+    let sw = Coord {
+        latitude: south.min(f32::from_bits(90f32.to_bits() - 1)),
+        longitude: wrap_longitude(west),
+    };
+    let ne = Coord {
+        latitude: north.min(f32::from_bits(90f32.to_bits() - 1)),
+        longitude: if east == 180f32 {
+            f32::from_bits(180f32.to_bits() - 1)
+        } else {
+            wrap_longitude(east)
+        },
+    };
+    Ok(cover_bbox(&sw, &ne, precision))
+}
+
+/// Returns the finest cell containing the whole box spanned by the south-west
+/// and north-east corners, or `None` if only the whole world does.
+pub fn enclosing_cell(sw: &Coord, ne: &Coord) -> Option<GeoBits> {
+    GeoBits::from(sw, 32).common_ancestor(&GeoBits::from(ne, 32))
+}
+
+/// Splits `cells` into those west of the prime meridian and those east of it,
+/// returned as `(negative, positive)` longitude halves. A cover crossing the
+/// antimeridian becomes two covers that each span a plain west-to-east range.
+pub fn split_antimeridian(cells: &[GeoBits]) -> (Vec<GeoBits>, Vec<GeoBits>) {
+    cells.iter().partition(|cell| {
+        let (_, lng) = cell.grid_index();
+        lng < 1 << (cell.precision() - 1)
+    })
+}
+
+/// Returns every cell at `precision` that intersects the circle of `radius_m`
+/// meters around `center`.
+pub fn cover_radius(center: &Coord, radius_m: f32, precision: u8) -> Vec<GeoBits> {
+    // Largest latitude below 90, since the valid latitude range is half-open.
+    let lat_max = f32::from_bits(90f32.to_bits() - 1);
+    let lat_span = (radius_m / EARTH_RADIUS_M).to_degrees();
+    let north = center.latitude + lat_span;
+    let south = center.latitude - lat_span;
+    // Near the poles the circle spans every longitude.
+    let lng_span = if north >= 90f32 || south <= -90f32 {
+        180f32
+    } else {
+        lat_span / north.abs().max(south.abs()).to_radians().cos()
+    };
+    let (west, east) = if lng_span >= 180f32 {
+        (-180f32, f32::from_bits(180f32.to_bits() - 1))
+    } else {
+        (
+            wrap_longitude(center.longitude - lng_span),
+            wrap_longitude(center.longitude + lng_span),
+        )
+    };
+    let sw = Coord {
+        latitude: south.max(-90f32),
+        longitude: west,
+    };
+    let ne = Coord {
+        latitude: north.min(lat_max),
+        longitude: east,
+    };
+    cover_bbox(&sw, &ne, precision)
+        .into_iter()
+        .filter(|cell| Area::from(*cell).distance_to(center) <= radius_m)
+        .collect()
+}
+
+/// Returns the cells at `precision` in the circular sector of `range_m` meters
+/// around `apex`, centered on `bearing_deg` (clockwise from north) and
+/// spreading `half_angle_deg` to either side. A cell is kept if it contains
+/// the apex or if its center or one of its corners lies in the sector, so very
+/// narrow sectors can slip between the sample points of large cells.
+pub fn cover_cone(
+    apex: &Coord,
+    bearing_deg: f32,
+    half_angle_deg: f32,
+    range_m: f32,
+    precision: u8,
+) -> Vec<GeoBits> {
+    let in_sector = |point: &Coord| {
+        if apex.haversine(point) > range_m {
+            return false;
+        }
+        let offset = (bearing(apex, point) - bearing_deg).rem_euclid(360f32);
+        offset.min(360f32 - offset) <= half_angle_deg
+    };
+    cover_radius(apex, range_m, precision)
+        .into_iter()
+        .filter(|cell| {
+            let area = Area::from(*cell);
+            if area.contains(apex) {
+                return true;
+            }
+            let corner = |latitude, longitude| Coord {
+                latitude,
+                longitude,
+            };
+            let (south, north) = (area.lat_range.start, area.lat_range.end);
+            let (west, east) = (area.lng_range.start, area.lng_range.end);
+            [
+                area.center(),
+                corner(south, west),
+                corner(south, east),
+                corner(north, west),
+                corner(north, east),
+            ]
+            .iter()
+            .any(in_sector)
+        })
+        .collect()
+}
+
+/// Returns the summed `area_m2` of `cells`, a cell-resolution estimate of the
+/// area of the region they cover. Overlapping cells are counted twice.
+pub fn cover_area_m2(cells: &[GeoBits]) -> f32 {
+    cells.iter().map(|cell| Area::from(*cell).area_m2()).sum()
+}
+
+/// Returns the total area of `cells` divided by the area of the circle of
+/// `radius_m` meters around `center`. Covers from `cover_radius` overshoot the
+/// circle, so this is at least 1.0 for them and approaches 1.0 as precision
+/// grows. The ratio only depends on the areas, so `_center` is unused.
+pub fn coverage_ratio(cells: &[GeoBits], _center: &Coord, radius_m: f32) -> f32 {
+    let covered = cover_area_m2(cells);
+    // Area of a spherical cap.
+    let circle = 2f32
+        * std::f32::consts::PI
+        * EARTH_RADIUS_M
+        * EARTH_RADIUS_M
+        * (1f32 - (radius_m / EARTH_RADIUS_M).cos());
+    covered / circle
+}
+
+/// Returns the Jaccard index of two covers: the number of cells in both
+/// divided by the number of cells in either. Cells are compared exactly, so a
+/// cell and its children count as different. Two empty covers are identical.
+pub fn cover_similarity(a: &[GeoBits], b: &[GeoBits]) -> f32 {
+    let a = sorted_unique(a);
+    let b = sorted_unique(b);
+    if a.is_empty() && b.is_empty() {
+        return 1f32;
+    }
+
+    // Merge the two Morton-sorted lists, counting cells present in both.
+    let (mut i, mut j, mut both) = (0, 0, 0);
+    while i < a.len() && j < b.len() {
+        let key_a = (a[i].morton_code(), a[i].precision);
+        let key_b = (b[j].morton_code(), b[j].precision);
+        match key_a.cmp(&key_b) {
+            std::cmp::Ordering::Less => i += 1,
+            std::cmp::Ordering::Greater => j += 1,
+            std::cmp::Ordering::Equal => {
+                both += 1;
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    both as f32 / (a.len() + b.len() - both) as f32
+}
+
+/// Returns a fingerprint of `cells` that does not depend on their order or
+/// on duplicates and is stable across runs and machines. The cells are
+/// sorted by Morton code, then each cell's big-endian Morton code followed by
+/// its precision byte is fed through 64-bit FNV-1a.
+pub fn cover_digest(cells: &[GeoBits]) -> u64 {
+    const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const FNV_PRIME: u64 = 0x100000001b3;
+    sorted_unique(cells)
+        .iter()
+        .flat_map(|cell| {
+            let mut bytes = [0u8; 9];
+            bytes[..8].copy_from_slice(&cell.morton_code().to_be_bytes());
+            bytes[8] = cell.precision();
+            bytes
+        })
+        .fold(FNV_OFFSET_BASIS, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(FNV_PRIME)
+        })
+}
+
+/// Returns the corners of the outline of the union of `cells`, in
+/// counter-clockwise order starting from the south-westmost corner, without
+/// repeating the first corner at the end. Corners where the outline runs
+/// straight on are left out, and a corner where two cells touch diagonally
+/// appears twice. The cells must share a precision and form one connected
+/// region without holes, otherwise the result is empty; the outline does not
+/// wrap around the antimeridian.
+pub fn cover_outline(cells: &[GeoBits]) -> Vec<Coord> {
+    let Some(first) = cells.first() else {
+        return Vec::new();
+    };
+    let precision = first.precision();
+    if cells.iter().any(|cell| cell.precision() != precision) {
+        return Vec::new();
+    }
+    // Each cell contributes its four edges counter-clockwise, as
+    // (from, to) grid vertices. An edge shared by two cells appears once in
+    // each direction and is interior.
+    let mut edges: HashSet<((u64, u64), (u64, u64))> = HashSet::new();
+    for cell in sorted_unique(cells) {
+        let (lat, lng) = cell.grid_index();
+        let (y, x) = (lat as u64, lng as u64);
+        let corners = [(y, x), (y, x + 1), (y + 1, x + 1), (y + 1, x)];
+        for i in 0..4 {
+            let edge = (corners[i], corners[(i + 1) % 4]);
+            if !edges.remove(&(edge.1, edge.0)) {
+                edges.insert(edge);
+            }
+        }
+    }
+    let total = edges.len();
+    let mut next: HashMap<(u64, u64), Vec<(u64, u64)>> = HashMap::new();
+    for (from, to) in edges {
+        next.entry(from).or_default().push(to);
+    }
+    let start = *next.keys().min().unwrap();
+
+    // Where cells touch only at a corner, two edges leave the same vertex.
+    // Turning right there keeps tracing the outline as one figure-of-eight
+    // instead of closing off the first cell's loop early.
+    let step = |from: (u64, u64), to: (u64, u64)| {
+        (to.0 as i64 - from.0 as i64, to.1 as i64 - from.1 as i64)
+    };
+    let mut ring = vec![start];
+    let mut heading = (0i64, 1i64);
+    let mut vertex = start;
+    while let Some(outgoing) = next.get_mut(&vertex).filter(|out| !out.is_empty()) {
+        let right = (-heading.1, heading.0);
+        let rank = |to: &(u64, u64)| {
+            let direction = step(vertex, *to);
+            if direction == right {
+                0
+            } else if direction == heading {
+                1
+            } else {
+                2
+            }
+        };
+        let chosen = (0..outgoing.len())
+            .min_by_key(|i| rank(&outgoing[*i]))
+            .unwrap();
+        let to = outgoing.swap_remove(chosen);
+        heading = step(vertex, to);
+        vertex = to;
+        if vertex == start {
+            break;
+        }
+        ring.push(vertex);
+    }
+    // Cells in separate pieces leave edges the walk never reached.
+    if ring.len() < total {
+        return Vec::new();
+    }
+    let len = ring.len();
+    let turns = (0..len).filter(|i| {
+        let (prev, here, after) = (ring[(i + len - 1) % len], ring[*i], ring[(i + 1) % len]);
+        let incoming = (here.0 as i64 - prev.0 as i64, here.1 as i64 - prev.1 as i64);
+        let outgoing = (
+            after.0 as i64 - here.0 as i64,
+            after.1 as i64 - here.1 as i64,
+        );
+        incoming.0.signum() != outgoing.0.signum() || incoming.1.signum() != outgoing.1.signum()
+    });
+    let scale = (1u64 << precision) as f64;
+    turns
+        .map(|i| Coord {
+            latitude: (ring[i].0 as f64 * 180f64 / scale - 90f64) as f32,
+            longitude: (ring[i].1 as f64 * 360f64 / scale - 180f64) as f32,
+        })
+        .collect()
+}
+
+/// Composes cover constraints, configured builder-style, and returns the
+/// cells at the query's precision that satisfy all of them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoverQuery {
+    precision: u8,
+    crossing_antimeridian: bool,
+    constraints: Vec<Constraint>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Constraint {
+    Bbox(Coord, Coord),
+    Radius(Coord, f32),
+}
+
+impl CoverQuery {
+    pub fn new(precision: u8) -> Self {
+        if precision == 0 || precision > 32 {
+            panic!("Precision should satisfy 1 <= precision <= 32");
+        }
+        CoverQuery {
+            precision,
+            crossing_antimeridian: true,
+            constraints: Vec::new(),
+        }
+    }
+
+    /// Keeps cells in the box spanned by the south-west and north-east corners,
+    /// as in `cover_bbox`.
+    pub fn within_bbox(mut self, sw: &Coord, ne: &Coord) -> Self {
+        self.constraints.push(Constraint::Bbox(*sw, *ne));
+        self
+    }
+
+    /// Keeps cells intersecting the circle of `radius_m` meters around
+    /// `center`, as in `cover_radius`.
+    pub fn within_radius(mut self, center: &Coord, radius_m: f32) -> Self {
+        self.constraints.push(Constraint::Radius(*center, radius_m));
+        self
+    }
+
+    /// Whether a box whose north-east corner is west of its south-west corner
+    /// wraps across the antimeridian, which is the default. When false, such a
+    /// box spans the longitudes between its corners the other way instead.
+    pub fn crossing_antimeridian(mut self, crossing: bool) -> Self {
+        self.crossing_antimeridian = crossing;
+        self
+    }
+
+    /// Returns the cells satisfying every constraint, in Morton order. A query
+    /// without constraints returns no cells rather than the whole world, which
+    /// has 4^precision of them; use `all_cells` for that.
+    pub fn collect(&self) -> Vec<GeoBits> {
+        let mut result: Option<HashSet<GeoBits>> = None;
+        for constraint in &self.constraints {
+            let cells: HashSet<GeoBits> = match constraint {
+                Constraint::Bbox(sw, ne) => {
+                    let (mut sw, mut ne) = (*sw, *ne);
+                    if !self.crossing_antimeridian && ne.longitude < sw.longitude {
+                        std::mem::swap(&mut sw.longitude, &mut ne.longitude);
+                    }
+                    cover_bbox(&sw, &ne, self.precision).into_iter().collect()
+                }
+                Constraint::Radius(center, radius_m) => {
+                    cover_radius(center, *radius_m, self.precision)
+                        .into_iter()
+                        .collect()
+                }
+            };
+            result = Some(match result {
+                Some(previous) => previous.intersection(&cells).copied().collect(),
+                None => cells,
+            });
+        }
+        let cells: Vec<GeoBits> = result.unwrap_or_default().into_iter().collect();
+        sorted_unique(&cells)
+    }
+}
+
+fn sorted_unique(cells: &[GeoBits]) -> Vec<GeoBits> {
+    let mut cells = cells.to_vec();
+    cells.sort_by_key(|cell| (cell.morton_code(), cell.precision));
+    cells.dedup();
+    cells
+}
+
+// Initial great-circle bearing from `from` to `to`, in degrees clockwise from
+// north in [0, 360).
+fn bearing(from: &Coord, to: &Coord) -> f32 {
+    let (lat1, lat2) = (
+        (from.latitude as f64).to_radians(),
+        (to.latitude as f64).to_radians(),
+    );
+    let dlng = ((to.longitude - from.longitude) as f64).to_radians();
+    let y = dlng.sin() * lat2.cos();
+    let x = lat1.cos() * lat2.sin() - lat1.sin() * lat2.cos() * dlng.cos();
+    (y.atan2(x).to_degrees() as f32).rem_euclid(360f32)
+}
+
+fn wrap_longitude(longitude: f32) -> f32 {
+    (longitude + 180f32).rem_euclid(360f32) - 180f32
+}
+
+/// Returns the cells at `precision` whose centers fall inside the simple
+/// polygon `ring`, using ray casting so that non-convex rings work. The ring
+/// may be open or closed and must not cross the antimeridian.
+pub fn cover_polygon(ring: &[Coord], precision: u8) -> Vec<GeoBits> {
+    if ring.len() < 3 {
+        return Vec::new();
+    }
+    let sw = Coord {
+        latitude: ring.iter().map(|c| c.latitude).fold(f32::MAX, f32::min),
+        longitude: ring.iter().map(|c| c.longitude).fold(f32::MAX, f32::min),
+    };
+    let ne = Coord {
+        latitude: ring.iter().map(|c| c.latitude).fold(f32::MIN, f32::max),
+        longitude: ring.iter().map(|c| c.longitude).fold(f32::MIN, f32::max),
+    };
+    cover_bbox(&sw, &ne, precision)
+        .into_iter()
+        .filter(|cell| point_in_polygon(&Area::from(*cell).center(), ring))
+        .collect()
+}
+
+/// Returns the candidate whose center is closest to `coord` by great-circle
+/// distance, or `None` if there are no candidates.
+pub fn nearest_cell(coord: &Coord, candidates: &[GeoBits]) -> Option<GeoBits> {
+    candidates.iter().copied().min_by(|a, b| {
+        let a = Area::from(*a).center().haversine(coord);
+        let b = Area::from(*b).center().haversine(coord);
+        a.total_cmp(&b)
+    })
+}
+
+// Counts crossings of a ray cast eastward from `point`.
+fn point_in_polygon(point: &Coord, ring: &[Coord]) -> bool {
+    let mut inside = false;
+    let mut j = ring.len() - 1;
+    for i in 0..ring.len() {
+        let (a, b) = (&ring[i], &ring[j]);
+        if (a.latitude > point.latitude) != (b.latitude > point.latitude) {
+            let lng = a.longitude
+                + (point.latitude - a.latitude) / (b.latitude - a.latitude)
+                    * (b.longitude - a.longitude);
+            if point.longitude < lng {
+                inside = !inside;
+            }
+        }
+        j = i;
+    }
+    inside
+}
+
+/// Repeatedly replaces any complete set of four siblings with their parent and
+/// drops cells already covered by a coarser one, returning the smallest set of
+/// cells covering the same region, in Morton order.
+pub fn compress(cells: &[GeoBits]) -> Vec<GeoBits> {
+    let mut set: HashSet<GeoBits> = cells.iter().copied().collect();
+
+    // Work from the finest precision up so that merged parents can themselves
+    // be merged at the next level.
+    for precision in (2..=32).rev() {
+        let mut families: HashMap<GeoBits, u8> = HashMap::new();
+        for cell in set.iter().filter(|cell| cell.precision == precision) {
+            if let Some(parent) = cell.parent() {
+                *families.entry(parent).or_insert(0) += 1;
+            }
+        }
+        for (parent, count) in families {
+            if count == 4 {
+                for child in parent.children() {
+                    set.remove(&child);
+                }
+                set.insert(parent);
+            }
+        }
+    }
+
+    let mut compressed: Vec<GeoBits> = set
+        .iter()
+        .filter(|cell| {
+            let mut ancestor = cell.parent();
+            while let Some(cell) = ancestor {
+                if set.contains(&cell) {
+                    return false;
+                }
+                ancestor = cell.parent();
+            }
+            true
+        })
+        .copied()
+        .collect();
+    compressed.sort_by_key(|cell| (cell.morton_code(), cell.precision));
+    compressed
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geohash::Direction;
+
+    use super::*;
+
+    #[test]
+    fn all_cells() {
+        let cells: Vec<GeoBits> = super::all_cells(1).collect();
+        assert_eq!(cells.len(), 4);
+        let area: f32 = cells.iter().map(|cell| Area::from(*cell).area_m2()).sum();
+        let sphere = 4.0 * std::f32::consts::PI * EARTH_RADIUS_M * EARTH_RADIUS_M;
+        assert!((area / sphere - 1.0).abs() < 1e-4);
+        for corner in [
+            (-89.0, -179.0),
+            (89.0, -179.0),
+            (-89.0, 179.0),
+            (89.0, 179.0),
+        ] {
+            let coord = Coord::new(corner.0, corner.1);
+            assert!(cells.iter().any(|cell| Area::from(*cell).contains(&coord)));
+        }
+
+        assert_eq!(super::all_cells(3).count(), 64);
+        assert_eq!(super::all_cells(32).nth(5).unwrap().bits, 5);
+    }
+
+    #[test]
+    fn cover_bbox() {
+        let cells = super::cover_bbox(&Coord::new(10.0, 20.0), &Coord::new(12.0, 23.0), 8);
+        assert!(!cells.is_empty());
+        for lat in [10.0, 11.0, 11.99] {
+            for lng in [20.0, 21.5, 22.99] {
+                let coord = Coord::new(lat, lng);
+                assert!(cells.contains(&GeoBits::from(&coord, 8)));
+            }
+        }
+        assert!(!cells.contains(&GeoBits::from(&Coord::new(15.0, 21.0), 8)));
+
+        let wrapped = super::cover_bbox(&Coord::new(0.0, 179.0), &Coord::new(1.0, -179.0), 8);
+        assert!(wrapped.contains(&GeoBits::from(&Coord::new(0.5, 179.5), 8)));
+        assert!(wrapped.contains(&GeoBits::from(&Coord::new(0.5, -179.5), 8)));
+        assert!(!wrapped.contains(&GeoBits::from(&Coord::new(0.5, 0.0), 8)));
+    }
+
+    #[test]
+    fn split_antimeridian() {
+        let cells = super::cover_bbox(&Coord::new(0.0, 179.0), &Coord::new(1.0, -179.0), 8);
+        let (negative, positive) = super::split_antimeridian(&cells);
+        assert!(!negative.is_empty() && !positive.is_empty());
+        assert_eq!(negative.len() + positive.len(), cells.len());
+        for cell in &negative {
+            assert!(Area::from(*cell).lng_range.end <= 0.0);
+        }
+        for cell in &positive {
+            assert!(Area::from(*cell).lng_range.start >= 0.0);
+        }
+        assert!(negative.contains(&GeoBits::from(&Coord::new(0.5, -179.5), 8)));
+        assert!(positive.contains(&GeoBits::from(&Coord::new(0.5, 179.5), 8)));
+    }
+
+    #[test]
+    fn cover_digest() {
+        let cells = super::cover_bbox(&Coord::new(25.0, 121.0), &Coord::new(25.5, 121.5), 12);
+        let mut shuffled = cells.clone();
+        shuffled.reverse();
+        shuffled.swap(0, 7);
+        shuffled.push(cells[3]);
+        assert_eq!(super::cover_digest(&cells), super::cover_digest(&shuffled));
+        assert_ne!(
+            super::cover_digest(&cells),
+            super::cover_digest(&cells[1..])
+        );
+        // The same bits at another precision are another cell.
+        let cell = GeoBits::from(&Coord::new(0.0, 0.0), 1);
+        let finer = cell.children()[0];
+        assert_ne!(super::cover_digest(&[cell]), super::cover_digest(&[finer]));
+        assert_eq!(super::cover_digest(&[]), 0xcbf29ce484222325);
+    }
+
+    #[test]
+    fn cover_cone() {
+        let apex = Coord::new(25.0, 121.0);
+        let cone = super::cover_cone(&apex, 0.0, 30.0, 50_000.0, 12);
+        let circle = super::cover_radius(&apex, 50_000.0, 12);
+        assert!(!cone.is_empty() && cone.len() < circle.len() / 3);
+        assert!(cone.contains(&GeoBits::from(&apex, 12)));
+        assert!(cone.contains(&GeoBits::from(&Coord::new(25.3, 121.0), 12)));
+        // Behind the apex, and off to the side.
+        assert!(!cone.contains(&GeoBits::from(&Coord::new(24.7, 121.0), 12)));
+        assert!(!cone.contains(&GeoBits::from(&Coord::new(25.0, 121.3), 12)));
+
+        // Looking east across the antimeridian.
+        let apex = Coord::new(0.0, 179.9);
+        let cone = super::cover_cone(&apex, 90.0, 20.0, 50_000.0, 12);
+        assert!(cone.contains(&GeoBits::from(&Coord::new(0.0, -179.8), 12)));
+        assert!(!cone.contains(&GeoBits::from(&Coord::new(0.0, 179.6), 12)));
+    }
+
+    #[test]
+    fn cover_area_m2() {
+        let cell = GeoBits::from(&Coord::new(0.0, 0.0), 10);
+        let block = [
+            cell,
+            cell.get_neighbor(Direction::South),
+            cell.get_neighbor(Direction::West),
+            cell.get_neighbor(Direction::SouthWest),
+        ];
+        let ratio = super::cover_area_m2(&block) / Area::from(cell).area_m2();
+        assert!((ratio - 4.0).abs() < 1e-3);
+        assert_eq!(super::cover_area_m2(&[]), 0.0);
+    }
+
+    #[test]
+    fn cover_query() {
+        let sw = Coord::new(25.0, 121.0);
+        let ne = Coord::new(25.5, 121.5);
+        let center = Coord::new(25.5, 121.5);
+        let cells = CoverQuery::new(10)
+            .within_bbox(&sw, &ne)
+            .within_radius(&center, 20_000.0)
+            .collect();
+        let bbox = super::cover_bbox(&sw, &ne, 10);
+        let circle = super::cover_radius(&center, 20_000.0, 10);
+        assert!(!cells.is_empty());
+        assert!(cells.len() < bbox.len() && cells.len() < circle.len());
+        for cell in &cells {
+            assert!(bbox.contains(cell) && circle.contains(cell));
+        }
+        assert_eq!(
+            CoverQuery::new(10).within_bbox(&sw, &ne).collect(),
+            sorted_unique(&bbox)
+        );
+
+        let west = Coord::new(0.0, 170.0);
+        let east = Coord::new(1.0, -170.0);
+        let wrapped = CoverQuery::new(8).within_bbox(&west, &east).collect();
+        let spanning = CoverQuery::new(8)
+            .within_bbox(&west, &east)
+            .crossing_antimeridian(false)
+            .collect();
+        assert!(wrapped.contains(&GeoBits::from(&Coord::new(0.5, 179.0), 8)));
+        assert!(spanning.contains(&GeoBits::from(&Coord::new(0.5, 0.0), 8)));
+        assert!(spanning.len() > wrapped.len());
+        assert!(CoverQuery::new(32).collect().is_empty());
+    }
+
+    #[test]
+    fn cover_outline() {
+        let cell = GeoBits::from(&Coord::new(25.006, 121.46), 10);
+        let block = [
+            cell,
+            cell.get_neighbor(Direction::North),
+            cell.get_neighbor(Direction::East),
+            cell.get_neighbor(Direction::NorthEast),
+        ];
+        let sw = cell.to_coord(crate::geohash::Anchor::SouthWest);
+        let ne = block[3].to_coord(crate::geohash::Anchor::NorthEast);
+        assert_eq!(
+            super::cover_outline(&block),
+            vec![
+                sw,
+                Coord::new(sw.latitude, ne.longitude),
+                ne,
+                Coord::new(ne.latitude, sw.longitude),
+            ]
+        );
+
+        // An L shape has six corners.
+        let ell = [cell, block[1], block[2]];
+        assert_eq!(super::cover_outline(&ell).len(), 6);
+
+        // Cells touching at a corner trace a figure-of-eight through it.
+        let pinch = [cell, block[3]];
+        let outline = super::cover_outline(&pinch);
+        assert_eq!(outline.len(), 8);
+        let middle = cell.to_coord(crate::geohash::Anchor::NorthEast);
+        assert_eq!(
+            outline.iter().filter(|corner| **corner == middle).count(),
+            2
+        );
+
+        // Separate pieces have no single outline.
+        let apart = [
+            cell,
+            cell.get_neighbor(Direction::East)
+                .get_neighbor(Direction::East),
+        ];
+        assert!(super::cover_outline(&apart).is_empty());
+        assert!(super::cover_outline(&[]).is_empty());
+
+        // Grid indices at different precisions name unrelated places.
+        let (y, x) = cell.grid_index();
+        let mixed = [cell, GeoBits::from_grid_index(y, x + 1, 12).unwrap()];
+        assert!(super::cover_outline(&mixed).is_empty());
+    }
+
+    #[test]
+    fn cover_polygon() {
+        let triangle = [
+            Coord::new(0.0, 0.0),
+            Coord::new(0.0, 10.0),
+            Coord::new(10.0, 0.0),
+        ];
+        let cells = super::cover_polygon(&triangle, 8);
+        assert!(!cells.is_empty());
+        for cell in &cells {
+            let center = Area::from(*cell).center();
+            assert!(center.latitude + center.longitude < 10.0);
+        }
+        assert!(cells.contains(&GeoBits::from(&Coord::new(2.0, 2.0), 8)));
+        assert!(!cells.contains(&GeoBits::from(&Coord::new(8.0, 8.0), 8)));
+
+        let l_shape = [
+            Coord::new(0.0, 0.0),
+            Coord::new(0.0, 10.0),
+            Coord::new(5.0, 10.0),
+            Coord::new(5.0, 5.0),
+            Coord::new(10.0, 5.0),
+            Coord::new(10.0, 0.0),
+        ];
+        let cells = super::cover_polygon(&l_shape, 8);
+        assert!(cells.contains(&GeoBits::from(&Coord::new(2.0, 8.0), 8)));
+        assert!(cells.contains(&GeoBits::from(&Coord::new(8.0, 2.0), 8)));
+        assert!(!cells.contains(&GeoBits::from(&Coord::new(8.0, 8.0), 8)));
+    }
+
+    #[test]
+    fn compress() {
+        let parent = GeoBits::from(&Coord::new(25.006, 121.46), 10);
+        let children = parent.children();
+        assert_eq!(super::compress(&children), vec![parent]);
+
+        let partial = &children[..3];
+        let mut expected = partial.to_vec();
+        expected.sort_by_key(|cell| cell.morton_code());
+        assert_eq!(super::compress(partial), expected);
+
+        // Grandchildren collapse all the way up, and cells inside an existing
+        // cell are dropped.
+        let mut cells: Vec<GeoBits> = children.iter().flat_map(|child| child.children()).collect();
+        cells.push(children[0].children()[0]);
+        assert_eq!(super::compress(&cells), vec![parent]);
+    }
+
+    #[test]
+    fn nearest_cell() {
+        let taipei = GeoBits::from(&Coord::new(25.033, 121.565), 10);
+        let tokyo = GeoBits::from(&Coord::new(35.6762, 139.6503), 10);
+        let sydney = GeoBits::from(&Coord::new(-33.87, 151.21), 10);
+        let candidates = [taipei, tokyo, sydney];
+        assert_eq!(
+            super::nearest_cell(&Coord::new(34.69, 135.50), &candidates),
+            Some(tokyo)
+        );
+        assert_eq!(
+            super::nearest_cell(&Coord::new(22.3, 114.17), &candidates),
+            Some(taipei)
+        );
+        assert_eq!(super::nearest_cell(&Coord::new(0.0, 0.0), &[]), None);
+    }
+
+    #[test]
+    fn cover_radius() {
+        let center = Coord::new(25.033, 121.565);
+        let cells = super::cover_radius(&center, 5_000.0, 14);
+        assert!(cells.contains(&GeoBits::from(&center, 14)));
+        assert!(cells.contains(&GeoBits::from(&Coord::new(25.07, 121.565), 14)));
+        assert!(!cells.contains(&GeoBits::from(&Coord::new(25.1, 121.565), 14)));
+    }
+
+    #[test]
+    fn coverage_ratio() {
+        let center = Coord::new(25.033, 121.565);
+        let coarse = super::cover_radius(&center, 5_000.0, 12);
+        let fine = super::cover_radius(&center, 5_000.0, 16);
+        let coarse_ratio = super::coverage_ratio(&coarse, &center, 5_000.0);
+        let fine_ratio = super::coverage_ratio(&fine, &center, 5_000.0);
+        assert!(coarse_ratio > fine_ratio);
+        assert!(fine_ratio > 1.0 && fine_ratio < 1.2);
+    }
+
+    #[test]
+    fn cover_similarity() {
+        let a = super::cover_bbox(&Coord::new(10.0, 20.0), &Coord::new(12.0, 23.0), 8);
+        let b = super::cover_bbox(&Coord::new(-10.0, -20.0), &Coord::new(-8.0, -17.0), 8);
+        let mut shuffled = a.clone();
+        shuffled.reverse();
+        assert_eq!(super::cover_similarity(&a, &shuffled), 1.0);
+        assert_eq!(super::cover_similarity(&a, &b), 0.0);
+
+        let half = &a[..a.len() / 2];
+        let similarity = super::cover_similarity(&a, half);
+        assert!((similarity - half.len() as f32 / a.len() as f32).abs() < 1e-6);
+    }
+
+    #[test]
+    fn cover_geojson_bbox() {
+        let cells = super::cover_geojson_bbox([20.0, 10.0, 23.0, 12.0], 8).unwrap();
+        assert_eq!(
+            cells,
+            super::cover_bbox(&Coord::new(10.0, 20.0), &Coord::new(12.0, 23.0), 8)
+        );
+
+        let dateline = super::cover_geojson_bbox([179.0, 0.0, -179.0, 1.0], 8).unwrap();
+        assert!(dateline.contains(&GeoBits::from(&Coord::new(0.5, 179.5), 8)));
+        assert!(dateline.contains(&GeoBits::from(&Coord::new(0.5, -179.5), 8)));
+        assert!(!dateline.contains(&GeoBits::from(&Coord::new(0.5, 0.0), 8)));
+
+        let world = super::cover_geojson_bbox([-180.0, -90.0, 180.0, 90.0], 2).unwrap();
+        assert_eq!(world.len(), 16);
+
+        assert_eq!(
+            super::cover_geojson_bbox([20.0, 12.0, 23.0, 10.0], 8),
+            Err(GeoError::InvalidLatitude(12.0))
+        );
+        assert_eq!(
+            super::cover_geojson_bbox([200.0, 10.0, 23.0, 12.0], 8),
+            Err(GeoError::InvalidLongitude(200.0))
+        );
+    }
+
+    #[test]
+    fn enclosing_cell() {
+        let sw = Coord::new(25.006, 121.46);
+        let ne = Coord::new(25.007, 121.461);
+        let cell = super::enclosing_cell(&sw, &ne).unwrap();
+        let area = Area::from(cell);
+        assert!(area.contains(&sw) && area.contains(&ne));
+        assert!(cell.precision >= 10);
+        // None of its children contain both corners.
+        for child in cell.children() {
+            let area = Area::from(child);
+            assert!(!(area.contains(&sw) && area.contains(&ne)));
+        }
+
+        assert_eq!(
+            super::enclosing_cell(&Coord::new(-1.0, 10.0), &Coord::new(1.0, 11.0)),
+            None
+        );
+    }
+}
@@ -0,0 +1,36 @@
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GeoError {
+    /// A latitude outside [-90, 90).
+    InvalidLatitude(f32),
+    /// A longitude outside [-180, 180).
+    InvalidLongitude(f32),
+    /// A geohash string contained a character outside its alphabet.
+    InvalidCharacter(char),
+    /// A geohash string was empty or too long to fit in a `GeoBits`.
+    InvalidLength(usize),
+    /// A precision outside 1..=32.
+    InvalidPrecision(u8),
+    /// A grid index that does not fit in the given precision.
+    InvalidGridIndex(u32),
+}
+
+impl fmt::Display for GeoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GeoError::InvalidLatitude(lat) => write!(f, "latitude {} is out of range", lat),
+            GeoError::InvalidLongitude(lng) => write!(f, "longitude {} is out of range", lng),
+            GeoError::InvalidCharacter(c) => write!(f, "invalid geohash character {:?}", c),
+            GeoError::InvalidLength(len) => write!(f, "invalid geohash length {}", len),
+            GeoError::InvalidPrecision(precision) => {
+                write!(f, "precision {} is not in 1..=32", precision)
+            }
+            GeoError::InvalidGridIndex(index) => {
+                write!(f, "grid index {} is out of range for the precision", index)
+            }
+        }
+    }
+}
+
+impl std::error::Error for GeoError {}
@@ -1,13 +1,27 @@
 use std::collections::HashMap;
 
-use crate::bits::{deinterleave64, interleave64};
+use crate::bits::{deinterleave64, deinterleave_lat, deinterleave_lng, interleave64};
+use crate::error::GeoError;
 use std::ops::Range;
+use std::sync::OnceLock;
 
 const LAT_MIN: f32 = -90f32;
 const LAT_MAX: f32 = 90f32;
 const LNG_MIN: f32 = -180f32;
 const LNG_MAX: f32 = 180f32;
 
+/// Mean Earth radius in meters.
+pub const EARTH_RADIUS_M: f32 = 6_371_008.8;
+
+/// Cells about 19.5 km tall and 39 km wide at the equator.
+pub const PRECISION_CITY: u8 = 10;
+/// Cells about 76 m tall and 153 m wide at the equator.
+pub const PRECISION_STREET: u8 = 18;
+/// Cells about 4.8 m tall and 9.5 m wide at the equator.
+pub const PRECISION_BUILDING: u8 = 22;
+/// Precision used when none is given.
+pub const DEFAULT_PRECISION: u8 = PRECISION_STREET;
+
 const LAT_RNG: Range<f32> = Range {
     start: LAT_MIN,
     end: LAT_MAX,
@@ -29,10 +43,10 @@ pub enum Direction {
     NorthWest,
 }
 
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Debug, Clone, Copy)]
 pub struct Coord {
-    latitude: f32,
-    longitude: f32,
+    pub(crate) latitude: f32,
+    pub(crate) longitude: f32,
 }
 
 impl Coord {
@@ -49,12 +63,136 @@ impl Coord {
         }
     }
 
+    /// Builds a coordinate from `(degrees, minutes, seconds)` triples, with
+    /// the hemisphere carried by the sign of the degrees. Positions within a
+    /// degree south of the equator or west of the prime meridian cannot be
+    /// written this way, since `-0` degrees is not an `i32`.
+    pub fn from_dms(lat_dms: (i32, u32, f32), lng_dms: (i32, u32, f32)) -> Result<Coord, GeoError> {
+        let latitude = dms_to_degrees(lat_dms);
+        if !is_valid_dms(lat_dms) {
+            return Err(GeoError::InvalidLatitude(latitude));
+        }
+        let longitude = dms_to_degrees(lng_dms);
+        if !is_valid_dms(lng_dms) {
+            return Err(GeoError::InvalidLongitude(longitude));
+        }
+        Coord::try_from((latitude, longitude))
+    }
+
+    /// Encodes this coordinate at the coarsest precision whose cell has no
+    /// corner farther than `max_error_m` meters from its center, so decoding
+    /// to the center is off by at most that much. Falls back to precision 32
+    /// if even that is too coarse.
+    pub fn encode_for_error(&self, max_error_m: f32) -> GeoBits {
+        (1..=32)
+            .map(|precision| GeoBits::from(self, precision))
+            .find(|hash| Area::from(*hash).half_diagonal_meters() <= max_error_m)
+            .unwrap_or_else(|| GeoBits::from(self, 32))
+    }
+
+    /// Encodes this coordinate and returns the cell's area along with it,
+    /// reusing the grid indices instead of deinterleaving them again.
+    pub fn encode_with_area(&self, precision: u8) -> (GeoBits, Area) {
+        if precision == 0 || precision > 32 {
+            panic!("Precision should satisfy 1 <= precision <= 32");
+        }
+        let (lat, lng) = grid_position(self, precision);
+        let (lat, lng) = (lat as u32, lng as u32);
+        let hash = GeoBits {
+            bits: interleave64(lat, lng),
+            precision,
+        };
+        let area = Area {
+            lat_range: index_range(lat, precision, &LAT_RNG),
+            lng_range: index_range(lng, precision, &LNG_RNG),
+        };
+        (hash, area)
+    }
+
     /// Computes the L2 distance, also known as the Euclidean distance.
     pub fn distance(&self, coord: &Coord) -> f32 {
         let lat_diff = self.latitude - coord.latitude;
         let lng_diff = self.longitude - coord.longitude;
         (lat_diff.powi(2) + lng_diff.powi(2)).sqrt()
     }
+
+    /// Computes the great-circle distance in meters using the haversine
+    /// formula.
+    pub fn haversine(&self, coord: &Coord) -> f32 {
+        let lat1 = (self.latitude as f64).to_radians();
+        let lat2 = (coord.latitude as f64).to_radians();
+        let lat_diff = lat2 - lat1;
+        let lng_diff = (coord.longitude as f64 - self.longitude as f64).to_radians();
+        let h = (lat_diff / 2f64).sin().powi(2)
+            + lat1.cos() * lat2.cos() * (lng_diff / 2f64).sin().powi(2);
+        // Rounding can push h just outside [0, 1] for identical or antipodal
+        // points, where asin would return NaN.
+        let h = h.clamp(0f64, 1f64);
+        (2f64 * EARTH_RADIUS_M as f64 * h.sqrt().asin()) as f32
+    }
+
+    /// Spherical linear interpolation along the great circle from `self`
+    /// (`t = 0`) to `other` (`t = 1`). The path between antipodal points is
+    /// not unique, so `self` is returned for them.
+    pub fn interpolate(&self, other: &Coord, t: f32) -> Coord {
+        let a = self.to_unit_vector();
+        let b = other.to_unit_vector();
+        let dot = (a[0] * b[0] + a[1] * b[1] + a[2] * b[2]).clamp(-1f64, 1f64);
+        let omega = dot.acos();
+        let sin_omega = omega.sin();
+        if sin_omega.abs() < 1e-12 {
+            return *self;
+        }
+        let t = t as f64;
+        let wa = ((1f64 - t) * omega).sin() / sin_omega;
+        let wb = (t * omega).sin() / sin_omega;
+        Coord::from_unit_vector([
+            wa * a[0] + wb * b[0],
+            wa * a[1] + wb * b[1],
+            wa * a[2] + wb * b[2],
+        ])
+    }
+
+    /// Returns the great-circle midpoint between two coordinates, which
+    /// differs from the lat/lng average over long distances or across the
+    /// antimeridian.
+    pub fn midpoint(&self, other: &Coord) -> Coord {
+        self.interpolate(other, 0.5)
+    }
+
+    pub(crate) fn to_unit_vector(self) -> [f64; 3] {
+        let lat = (self.latitude as f64).to_radians();
+        let lng = (self.longitude as f64).to_radians();
+        [lat.cos() * lng.cos(), lat.cos() * lng.sin(), lat.sin()]
+    }
+
+    pub(crate) fn from_unit_vector(v: [f64; 3]) -> Coord {
+        let latitude = v[2].atan2(v[0].hypot(v[1])).to_degrees() as f32;
+        // The north pole itself is outside the half-open latitude range.
+        let latitude = latitude.min(LAT_MAX.next_down());
+        let mut longitude = v[1].atan2(v[0]).to_degrees() as f32;
+        // atan2 returns (-180, 180], but the valid range excludes 180.
+        if longitude >= LNG_MAX {
+            longitude -= LNG_RNG.length();
+        }
+        Coord {
+            latitude,
+            longitude,
+        }
+    }
+}
+
+fn dms_to_degrees((degrees, minutes, seconds): (i32, u32, f32)) -> f32 {
+    let magnitude = degrees.unsigned_abs() as f64 + minutes as f64 / 60.0 + seconds as f64 / 3600.0;
+    if degrees < 0 {
+        -magnitude as f32
+    } else {
+        magnitude as f32
+    }
+}
+
+fn is_valid_dms((_, minutes, seconds): (i32, u32, f32)) -> bool {
+    minutes < 60 && (0f32..60f32).contains(&seconds)
 }
 
 pub trait RangeExtension {
@@ -77,9 +215,10 @@ impl RangeExtension for Range<f32> {
     }
 }
 
+#[derive(Debug, Clone, PartialEq)]
 pub struct Area {
-    lat_range: Range<f32>,
-    lng_range: Range<f32>,
+    pub(crate) lat_range: Range<f32>,
+    pub(crate) lng_range: Range<f32>,
 }
 
 impl Area {
@@ -93,12 +232,183 @@ impl Area {
     pub fn contains(&self, coord: &Coord) -> bool {
         self.lat_range.contains(&coord.latitude) && self.lng_range.contains(&coord.longitude)
     }
+
+    /// Clips the area to latitudes [-90, 90] and longitudes [-180, 180].
+    pub fn clamp_to_world(&self) -> Area {
+        let clamp = |range: &Range<f32>, world: &Range<f32>| Range {
+            start: range.start.clamp(world.start, world.end),
+            end: range.end.clamp(world.start, world.end),
+        };
+        Area {
+            lat_range: clamp(&self.lat_range, &LAT_RNG),
+            lng_range: clamp(&self.lng_range, &LNG_RNG),
+        }
+    }
+
+    /// Splits the area into a `rows` by `cols` grid of equal lat/lng
+    /// rectangles, row by row from the south-west.
+    pub fn subdivide(&self, rows: u32, cols: u32) -> Vec<Area> {
+        let lat_edges = split_range(&self.lat_range, rows);
+        let lng_edges = split_range(&self.lng_range, cols);
+        let mut areas = Vec::with_capacity(rows as usize * cols as usize);
+        for lat in lat_edges.windows(2) {
+            for lng in lng_edges.windows(2) {
+                areas.push(Area {
+                    lat_range: lat[0]..lat[1],
+                    lng_range: lng[0]..lng[1],
+                });
+            }
+        }
+        areas
+    }
+
+    // Distance from the center to the farthest corner, which for cells away
+    // from the equator is one of the two closer to it.
+    fn half_diagonal_meters(&self) -> f32 {
+        let center = self.center();
+        [
+            (self.lat_range.start, self.lng_range.start),
+            (self.lat_range.start, self.lng_range.end),
+            (self.lat_range.end, self.lng_range.start),
+            (self.lat_range.end, self.lng_range.end),
+        ]
+        .iter()
+        .map(|(latitude, longitude)| {
+            center.haversine(&Coord {
+                latitude: *latitude,
+                longitude: *longitude,
+            })
+        })
+        .fold(0f32, f32::max)
+    }
+
+    /// Returns the fraction of this area's surface that lies inside `other`,
+    /// in [0, 1]. Both areas are taken as plain lat/lng rectangles, so neither
+    /// should cross the antimeridian.
+    pub fn overlap_fraction(&self, other: &Area) -> f32 {
+        let lat_start = self.lat_range.start.max(other.lat_range.start);
+        let lat_end = self.lat_range.end.min(other.lat_range.end);
+        let lng_start = self.lng_range.start.max(other.lng_range.start);
+        let lng_end = self.lng_range.end.min(other.lng_range.end);
+        let total = self.area_m2();
+        if lat_start >= lat_end || lng_start >= lng_end || total <= 0f32 {
+            return 0f32;
+        }
+        let overlap = Area {
+            lat_range: lat_start..lat_end,
+            lng_range: lng_start..lng_end,
+        };
+        (overlap.area_m2() / total).min(1f32)
+    }
+
+    /// Returns true if the centers of both areas fall in the same cell at
+    /// `precision`, which tolerates the float noise between decoding paths.
+    pub fn eq_at_precision(&self, other: &Area, precision: u8) -> bool {
+        !distinguishes(&self.center(), &other.center(), precision)
+    }
+
+    /// Returns the corners in whole microdegrees, rounded to nearest, as
+    /// `(south, west, north, east)`.
+    pub fn to_microdegrees(&self) -> (i32, i32, i32, i32) {
+        let micro = |degrees: f32| (degrees as f64 * 1e6).round() as i32;
+        (
+            micro(self.lat_range.start),
+            micro(self.lng_range.start),
+            micro(self.lat_range.end),
+            micro(self.lng_range.end),
+        )
+    }
+
+    /// Returns the centers of the `subdivide(rows, cols)` grid, row by row
+    /// from the south-west.
+    pub fn sample_grid(&self, rows: u32, cols: u32) -> Vec<Coord> {
+        self.subdivide(rows, cols)
+            .iter()
+            .map(Area::center)
+            .collect()
+    }
+
+    /// Returns the surface area in square meters, treating the Earth as a
+    /// sphere.
+    pub fn area_m2(&self) -> f32 {
+        let lat_start = (self.lat_range.start as f64).to_radians();
+        let lat_end = (self.lat_range.end as f64).to_radians();
+        let lng_span = (self.lng_range.length() as f64).to_radians();
+        let radius = EARTH_RADIUS_M as f64;
+        (radius * radius * lng_span * (lat_end.sin() - lat_start.sin())) as f32
+    }
+
+    /// Returns the great-circle distance in meters from `coord` to the nearest
+    /// edge or corner of the area, or 0 if `coord` is inside it. Longitudes
+    /// are compared the short way around the antimeridian.
+    pub fn distance_to(&self, coord: &Coord) -> f32 {
+        let latitude = coord
+            .latitude
+            .clamp(self.lat_range.start, self.lat_range.end);
+        let mut longitude = coord.longitude;
+        let center = self.lng_range.center();
+        if longitude - center > 180f32 {
+            longitude -= 360f32;
+        } else if center - longitude > 180f32 {
+            longitude += 360f32;
+        }
+        let longitude = longitude.clamp(self.lng_range.start, self.lng_range.end);
+        coord.haversine(&Coord {
+            latitude,
+            longitude,
+        })
+    }
+
+    /// Approximates the area in square meters shared by this area and the
+    /// circle of `radius_m` meters around `center`. Areas entirely outside
+    /// the circle give 0 and areas whose corners are all inside give their
+    /// full `area_m2`; otherwise the area is split into a 32 by 32 grid and
+    /// the pieces whose centers lie in the circle are summed.
+    pub fn circle_overlap_m2(&self, center: &Coord, radius_m: f32) -> f32 {
+        if self.distance_to(center) > radius_m {
+            return 0f32;
+        }
+        let corners = [
+            (self.lat_range.start, self.lng_range.start),
+            (self.lat_range.start, self.lng_range.end),
+            (self.lat_range.end, self.lng_range.start),
+            (self.lat_range.end, self.lng_range.end),
+        ];
+        if corners.iter().all(|(latitude, longitude)| {
+            let corner = Coord {
+                latitude: *latitude,
+                longitude: *longitude,
+            };
+            center.haversine(&corner) <= radius_m
+        }) {
+            return self.area_m2();
+        }
+        self.subdivide(32, 32)
+            .iter()
+            .filter(|piece| center.haversine(&piece.center()) <= radius_m)
+            .map(Area::area_m2)
+            .sum()
+    }
+
+    /// Returns the great-circle distance from the south-west corner to the
+    /// north-east corner, a quick proxy for the size of the area.
+    pub fn diagonal_meters(&self) -> f32 {
+        let sw = Coord {
+            latitude: self.lat_range.start,
+            longitude: self.lng_range.start,
+        };
+        let ne = Coord {
+            latitude: self.lat_range.end,
+            longitude: self.lng_range.end,
+        };
+        sw.haversine(&ne)
+    }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct GeoBits {
-    bits: u64,
-    precision: u8,
+    pub(crate) bits: u64,
+    pub(crate) precision: u8,
 }
 
 pub type Neighbors = HashMap<Direction, GeoBits>;
@@ -106,23 +416,65 @@ pub type Neighbors = HashMap<Direction, GeoBits>;
 const LAT_BITS: u64 = 0x5555555555555555;
 const LNG_BITS: u64 = 0xAAAAAAAAAAAAAAAA;
 
+/// Decides which cell a coordinate lying exactly on a cell boundary belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BoundaryRule {
+    /// Boundary points belong to the cell to their south and west.
+    TowardSouthWest,
+    /// Boundary points belong to the cell to their north and east. Cells are
+    /// half-open, so this agrees with `Area::contains`.
+    #[default]
+    TowardNorthEast,
+}
+
+/// Which point of a cell represents it when decoding to a single coordinate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Anchor {
+    Center,
+    /// The corner most geohash libraries decode to.
+    SouthWest,
+    /// The exclusive upper corner, which belongs to the neighboring cell.
+    NorthEast,
+}
+
+/// How `GeoBits::from_with_rounding` turns a fractional grid position into a
+/// cell index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RoundingMode {
+    /// Round down, giving the cell that contains the coordinate. This is what
+    /// `GeoBits::from` does.
+    #[default]
+    Truncate,
+    /// Round to the nearest index, halves away from zero, giving the cell whose
+    /// south-west corner is closest to the coordinate.
+    Nearest,
+    /// Round to the nearest index, halves to the even index, which avoids a
+    /// systematic bias when many coordinates sit exactly on half steps.
+    BankersRounding,
+}
+
 impl GeoBits {
+    /// Encodes `coord` using `BoundaryRule::TowardNorthEast`.
     pub fn from(coord: &Coord, precision: u8) -> Self {
+        GeoBits::from_with_rule(coord, precision, BoundaryRule::default())
+    }
+
+    /// Encodes `coord`, resolving coordinates on a cell boundary with `rule`.
+    pub fn from_with_rule(coord: &Coord, precision: u8, rule: BoundaryRule) -> Self {
         if precision == 0 || precision > 32 {
             panic!("Precision should satisfy 1 <= precision <= 32");
         }
-        // Scale the coordinates to be between 0 and 1
-        let lat = (coord.latitude - LAT_MIN) / LAT_RNG.length();
-        let lng = (coord.longitude - LNG_MIN) / LNG_RNG.length();
+        let (lat, lng) = grid_position(coord, precision);
 
-        // Change the representation of these floats to fixed point. Since
-        // precision can be 32, we need u64.
-        let lat = (lat as f64) * ((1u64 << precision) as f64);
-        let lng = (lng as f64) * ((1u64 << precision) as f64);
-
-        // Now we have pure bits that we can interleave.
-        let lat = lat as u32;
-        let lng = lng as u32;
+        // Now we have pure bits that we can interleave. Truncating puts a
+        // boundary point in the cell it is the south-west corner of.
+        let (lat, lng) = match rule {
+            BoundaryRule::TowardNorthEast => (lat as u32, lng as u32),
+            BoundaryRule::TowardSouthWest => (
+                (lat.ceil() as u32).saturating_sub(1),
+                (lng.ceil() as u32).saturating_sub(1),
+            ),
+        };
 
         // Raw representation of geohash. Users can group every 5 bits and store
         // them as a hexadecimal string to implement the standard geohash.
@@ -131,17 +483,94 @@ impl GeoBits {
         GeoBits { bits, precision }
     }
 
+    /// Encodes a coordinate given in microdegrees using integer arithmetic
+    /// only, so the result is the same on every machine. Agrees with
+    /// `GeoBits::from` wherever the float encoder is exact.
+    pub fn encode_fixed(lat_micro: i32, lng_micro: i32, precision: u8) -> Self {
+        if precision == 0 || precision > 32 {
+            panic!("Precision should satisfy 1 <= precision <= 32");
+        }
+        if !(-90_000_000..90_000_000).contains(&lat_micro) {
+            panic!("latitude must be in [-90000000, 90000000) microdegrees.");
+        }
+        if !(-180_000_000..180_000_000).contains(&lng_micro) {
+            panic!("longitude must be in [-180000000, 180000000) microdegrees.");
+        }
+        // The offsets fit in 29 bits, so shifting by up to 32 fits in a u64.
+        let lat = ((lat_micro as i64 + 90_000_000) as u64) << precision;
+        let lng = ((lng_micro as i64 + 180_000_000) as u64) << precision;
+        GeoBits {
+            bits: interleave64((lat / 180_000_000) as u32, (lng / 360_000_000) as u32),
+            precision,
+        }
+    }
+
+    /// Encodes `coord`, choosing the cell index with `mode`. Anything but
+    /// `RoundingMode::Truncate` may return a cell that does not contain
+    /// `coord`, trading that for unbiased indices in aggregates.
+    pub fn from_with_rounding(coord: &Coord, precision: u8, mode: RoundingMode) -> Self {
+        if precision == 0 || precision > 32 {
+            panic!("Precision should satisfy 1 <= precision <= 32");
+        }
+        let (lat, lng) = grid_position(coord, precision);
+        let max = ((1u64 << precision) - 1) as f64;
+        let round = |x: f64| {
+            let index = match mode {
+                RoundingMode::Truncate => x.floor(),
+                RoundingMode::Nearest => x.round(),
+                RoundingMode::BankersRounding => x.round_ties_even(),
+            };
+            // Rounding up from the last row or column would leave the grid.
+            index.min(max) as u32
+        };
+        GeoBits {
+            bits: interleave64(round(lat), round(lng)),
+            precision,
+        }
+    }
+
+    /// Returns the cell whose area matches the box spanned by the south-west
+    /// and north-east corners, to within a thousandth of the cell's size, or
+    /// `None` if the box is not a cell.
+    pub fn try_from_bbox(sw: &Coord, ne: &Coord) -> Option<GeoBits> {
+        let lat_span = ne.latitude - sw.latitude;
+        if lat_span <= 0f32 {
+            return None;
+        }
+        let precision = (LAT_RNG.length() / lat_span).log2().round();
+        if !(1f32..=32f32).contains(&precision) {
+            return None;
+        }
+        let center = Coord {
+            latitude: sw.latitude + lat_span / 2f32,
+            longitude: sw.longitude + (ne.longitude - sw.longitude) / 2f32,
+        };
+        if !LAT_RNG.contains(&center.latitude) || !LNG_RNG.contains(&center.longitude) {
+            return None;
+        }
+        let hash = GeoBits::from(&center, precision as u8);
+        let area = Area::from(hash);
+        let tolerance = area.lat_range.length() / 1000f32;
+        let matches = (area.lat_range.start - sw.latitude).abs() <= tolerance
+            && (area.lat_range.end - ne.latitude).abs() <= tolerance
+            && (area.lng_range.start - sw.longitude).abs() <= tolerance
+            && (area.lng_range.end - ne.longitude).abs() <= tolerance;
+        matches.then_some(hash)
+    }
+
     fn move_x(&mut self, left: bool) -> &mut Self {
         let mut lng = self.bits & LNG_BITS;
         let lat = self.bits & LAT_BITS;
 
         let num_unused_bits = 64 - self.precision * 2;
         let tmp = LAT_BITS >> num_unused_bits;
+        // At precision 32 there are no unused bits to absorb the carry, so the
+        // arithmetic has to wrap around the u64 instead.
         if left {
             lng |= tmp;
-            lng -= tmp + 1;
+            lng = lng.wrapping_sub(tmp + 1);
         } else {
-            lng += tmp + 1;
+            lng = lng.wrapping_add(tmp + 1);
         }
         lng &= LNG_BITS >> num_unused_bits;
         self.bits = lng | lat;
@@ -156,15 +585,46 @@ impl GeoBits {
         let tmp = LNG_BITS >> num_unused_bits;
         if bottom {
             lat |= tmp;
-            lat -= tmp + 1;
+            lat = lat.wrapping_sub(tmp + 1);
         } else {
-            lat += tmp + 1;
+            lat = lat.wrapping_add(tmp + 1);
         }
         lat &= LAT_BITS >> num_unused_bits;
         self.bits = lng | lat;
         self
     }
 
+    /// Moves `dx` cells east and `dy` cells north in one step. Longitude wraps
+    /// around the antimeridian; returns `None` if the move passes a pole.
+    pub fn offset(&self, dx: i64, dy: i64) -> Option<GeoBits> {
+        let (lng, lat) = deinterleave64(self.bits);
+        let size = 1i64 << self.precision;
+        let lat = (lat as i64)
+            .checked_add(dy)
+            .filter(|lat| (0..size).contains(lat))?;
+        // Reducing `dx` first keeps huge moves from overflowing.
+        let lng = (lng as i64 + dx.rem_euclid(size)) % size;
+        Some(GeoBits {
+            bits: interleave64(lat as u32, lng as u32),
+            precision: self.precision,
+        })
+    }
+
+    /// Returns the cell on the opposite side of the Earth: latitude mirrored
+    /// across the equator and longitude shifted by 180°.
+    pub fn antipode(&self) -> GeoBits {
+        let (lng, lat) = deinterleave64(self.bits);
+        let size = 1u64 << self.precision;
+        let lat = size - 1 - lat as u64;
+        let lng = (lng as u64 + size / 2) % size;
+        GeoBits {
+            bits: interleave64(lat as u32, lng as u32),
+            precision: self.precision,
+        }
+    }
+
+    /// Returns all eight neighbors keyed by direction. Building the map is the
+    /// expensive part; use `neighbors_clockwise` when an array will do.
     pub fn get_neighbors(&self) -> Neighbors {
         Neighbors::from([
             (Direction::North, self.get_neighbor(Direction::North)),
@@ -190,22 +650,203 @@ impl GeoBits {
         ])
     }
 
+    /// Returns the eight neighbors clockwise from north: N, NE, E, SE, S, SW,
+    /// W, NW.
+    pub fn neighbors_clockwise(&self) -> [GeoBits; 8] {
+        [
+            self.get_neighbor(Direction::North),
+            self.get_neighbor(Direction::NorthEast),
+            self.get_neighbor(Direction::East),
+            self.get_neighbor(Direction::SouthEast),
+            self.get_neighbor(Direction::South),
+            self.get_neighbor(Direction::SouthWest),
+            self.get_neighbor(Direction::West),
+            self.get_neighbor(Direction::NorthWest),
+        ]
+    }
+
+    /// Lazily yields each neighbor with its direction, clockwise from north
+    /// like `neighbors_clockwise`. A neighbor is only computed when the
+    /// iterator reaches it.
+    pub fn neighbor_iter(&self) -> impl Iterator<Item = (Direction, GeoBits)> {
+        let center = *self;
+        let clockwise = |i| match i {
+            0 => Direction::North,
+            1 => Direction::NorthEast,
+            2 => Direction::East,
+            3 => Direction::SouthEast,
+            4 => Direction::South,
+            5 => Direction::SouthWest,
+            6 => Direction::West,
+            _ => Direction::NorthWest,
+        };
+        (0..8).map(move |i| (clockwise(i), center.get_neighbor(clockwise(i))))
+    }
+
+    /// Returns the distinct cells at `coarse_precision` that contain one of
+    /// this cell's eight neighbors, other than the one containing this cell,
+    /// in the clockwise order of `neighbor_iter`. A cell deep inside its
+    /// coarse ancestor has none; one in a corner of it has three. Longitude
+    /// wraps, but there are no neighbors beyond the poles.
+    pub fn coarse_neighbors(&self, coarse_precision: u8) -> Vec<GeoBits> {
+        if coarse_precision == 0 || coarse_precision > self.precision {
+            panic!("Coarse precision should satisfy 1 <= coarse_precision <= precision");
+        }
+        let shift = (self.precision - coarse_precision) as u32 * 2;
+        let ancestor = |cell: &GeoBits| GeoBits {
+            bits: cell.bits >> shift,
+            precision: coarse_precision,
+        };
+        let own = ancestor(self);
+        let mut cells: Vec<GeoBits> = Vec::new();
+        let offsets = [
+            (0, 1),
+            (1, 1),
+            (1, 0),
+            (1, -1),
+            (0, -1),
+            (-1, -1),
+            (-1, 0),
+            (-1, 1),
+        ];
+        for neighbor in offsets.iter().filter_map(|(dx, dy)| self.offset(*dx, *dy)) {
+            let coarse = ancestor(&neighbor);
+            if coarse != own && !cells.contains(&coarse) {
+                cells.push(coarse);
+            }
+        }
+        cells
+    }
+
+    /// Returns the cells at `neighbor_precision` that border this cell on the
+    /// side given by `direction`, running south to north along east and west
+    /// edges and west to east along north and south edges. Diagonal directions
+    /// return the single cell touching that corner. Longitude wraps; there are
+    /// no cells beyond the poles.
+    pub fn neighbors_along_edge(
+        &self,
+        direction: Direction,
+        neighbor_precision: u8,
+    ) -> Vec<GeoBits> {
+        if neighbor_precision < self.precision || neighbor_precision > 32 {
+            panic!("Neighbor precision should satisfy precision <= neighbor_precision <= 32");
+        }
+        let depth = (neighbor_precision - self.precision) as u32;
+        let (lat, lng) = self.grid_index();
+        let size = 1i64 << neighbor_precision;
+        let span = 1i64 << depth;
+        let south = (lat as i64) << depth;
+        let west = (lng as i64) << depth;
+        let (north, east) = (south + span - 1, west + span - 1);
+
+        let (rows, cols) = match direction {
+            Direction::North => (north + 1..=north + 1, west..=east),
+            Direction::South => (south - 1..=south - 1, west..=east),
+            Direction::East => (south..=north, east + 1..=east + 1),
+            Direction::West => (south..=north, west - 1..=west - 1),
+            Direction::NorthEast => (north + 1..=north + 1, east + 1..=east + 1),
+            Direction::SouthEast => (south - 1..=south - 1, east + 1..=east + 1),
+            Direction::SouthWest => (south - 1..=south - 1, west - 1..=west - 1),
+            Direction::NorthWest => (north + 1..=north + 1, west - 1..=west - 1),
+        };
+        let mut cells = Vec::new();
+        for row in rows.filter(|row| (0..size).contains(row)) {
+            for col in cols.clone() {
+                cells.push(GeoBits {
+                    bits: interleave64(row as u32, col.rem_euclid(size) as u32),
+                    precision: neighbor_precision,
+                });
+            }
+        }
+        cells
+    }
+
     pub fn get_neighbor(&self, direction: Direction) -> GeoBits {
-        let mut bits = GeoBits {
-            bits: self.bits,
+        let mut neighbor = *self;
+        match direction {
+            Direction::North => neighbor.move_y(false),
+            Direction::East => neighbor.move_x(false),
+            Direction::South => neighbor.move_y(true),
+            Direction::West => neighbor.move_x(true),
+            Direction::NorthEast => neighbor.move_y(false).move_x(false),
+            Direction::SouthEast => neighbor.move_y(true).move_x(false),
+            Direction::SouthWest => neighbor.move_y(true).move_x(true),
+            Direction::NorthWest => neighbor.move_y(false).move_x(true),
+        };
+        neighbor
+    }
+
+    /// Like `get_neighbor`, but moving north from the top row or south from
+    /// the bottom row goes over the pole: the result stays in the same row on
+    /// the opposite side of the globe, 180 degrees of longitude away, instead
+    /// of wrapping to the other pole.
+    pub fn get_neighbor_spherical(&self, direction: Direction) -> GeoBits {
+        let (dy, dx) = match direction {
+            Direction::North => (1, 0),
+            Direction::NorthEast => (1, 1),
+            Direction::East => (0, 1),
+            Direction::SouthEast => (-1, 1),
+            Direction::South => (-1, 0),
+            Direction::SouthWest => (-1, -1),
+            Direction::West => (0, -1),
+            Direction::NorthWest => (1, -1),
+        };
+        let (lat, lng) = self.grid_index();
+        let size = 1i64 << self.precision;
+        let mut lat = lat as i64 + dy;
+        let mut lng = lng as i64 + dx;
+        if !(0..size).contains(&lat) {
+            lat -= dy;
+            lng += size / 2;
+        }
+        GeoBits {
+            bits: interleave64(lat as u32, lng.rem_euclid(size) as u32),
             precision: self.precision,
+        }
+    }
+
+    /// Returns the neighbor in `direction` together with the length in meters
+    /// of the boundary the two cells share: a full side for cardinal
+    /// directions and 0 for diagonal ones, which only touch at a corner.
+    pub fn neighbor_with_edge(&self, direction: Direction) -> (GeoBits, f32) {
+        let area = Area::from(*self);
+        let radius = EARTH_RADIUS_M as f64;
+        let lat_span = (area.lat_range.length() as f64).to_radians();
+        let lng_span = (area.lng_range.length() as f64).to_radians();
+        let parallel = |latitude: f32| radius * lng_span * (latitude as f64).to_radians().cos();
+        let edge = match direction {
+            Direction::North => parallel(area.lat_range.end),
+            Direction::South => parallel(area.lat_range.start),
+            Direction::East | Direction::West => radius * lat_span,
+            _ => 0f64,
         };
-        match direction {
-            Direction::North => bits.move_y(false),
-            Direction::East => bits.move_x(false),
-            Direction::South => bits.move_y(true),
-            Direction::West => bits.move_x(true),
-            Direction::NorthEast => bits.move_y(false).move_x(false),
-            Direction::SouthEast => bits.move_y(true).move_x(false),
-            Direction::SouthWest => bits.move_y(true).move_x(true),
-            Direction::NorthWest => bits.move_y(false).move_x(true),
+        (self.get_neighbor(direction), edge as f32)
+    }
+
+    /// Returns the cell at `target_precision` containing the center of the
+    /// neighbor in `direction`. With a finer target this is the cell just
+    /// north-east of that center; with a coarser one, the ancestor of the
+    /// neighbor.
+    pub fn neighbor_at(&self, direction: Direction, target_precision: u8) -> GeoBits {
+        if target_precision == 0 || target_precision > 32 {
+            panic!("Precision should satisfy 1 <= precision <= 32");
+        }
+        let (lat, lng) = self.get_neighbor(direction).grid_index();
+        let (lat, lng) = if target_precision >= self.precision {
+            let depth = (target_precision - self.precision) as u32;
+            let half = (1u64 << depth) >> 1;
+            (
+                ((lat as u64) << depth) + half,
+                ((lng as u64) << depth) + half,
+            )
+        } else {
+            let depth = (self.precision - target_precision) as u32;
+            ((lat >> depth) as u64, (lng >> depth) as u64)
         };
-        bits
+        GeoBits {
+            bits: interleave64(lat as u32, lng as u32),
+            precision: target_precision,
+        }
     }
 
     pub fn next_leftbottom(&self) -> GeoBits {
@@ -235,113 +876,2082 @@ impl GeoBits {
             precision: self.precision + 1,
         }
     }
-}
 
-impl Into<Area> for GeoBits {
-    fn into(self) -> Area {
-        let (lng, lat) = deinterleave64(self.bits);
+    /// Decodes this cell to the point selected by `anchor`.
+    pub fn to_coord(&self, anchor: Anchor) -> Coord {
+        let area = Area::from(*self);
+        match anchor {
+            Anchor::Center => area.center(),
+            Anchor::SouthWest => Coord {
+                latitude: area.lat_range.start,
+                longitude: area.lng_range.start,
+            },
+            Anchor::NorthEast => Coord {
+                latitude: area.lat_range.end,
+                longitude: area.lng_range.end,
+            },
+        }
+    }
 
-        let lat_scale = 180f32;
-        let lng_scale = 360f32;
+    pub fn bits(&self) -> u64 {
+        self.bits
+    }
 
-        // Note that if we look at the latitude and longitude bits separately,
-        // each cell is +1 from the previous cell:
-        //
-        // |---------------|---------------|
-        //         0               1
-        // |-------|-------|-------|-------|
-        //     00      01      10      11
-        // |---|---|---|---|---|---|---|---|
-        //  000 001 010 011 100 101 110 111
-        //
-        // Thus, to get the upper bound of a geohash, you just need to +1 to the
-        // latitude bits and then convert the number back to floating point.
-        let float_scale = (1u32 << self.precision) as f32;
-        let lat_range = Range {
-            start: LAT_MIN + (lat as f32 / float_scale) * lat_scale,
-            end: LAT_MIN + ((lat + 1) as f32 / float_scale) * lat_scale,
-        };
-        let lng_range = Range {
-            start: LNG_MIN + (lng as f32 / float_scale) * lng_scale,
-            end: LNG_MIN + ((lng + 1) as f32 / float_scale) * lng_scale,
-        };
-        Area {
-            lat_range,
-            lng_range,
+    pub fn precision(&self) -> u8 {
+        self.precision
+    }
+
+    /// Builds the cell at the given grid indices, the inverse of `grid_index`.
+    pub fn from_grid_index(lat_idx: u32, lng_idx: u32, precision: u8) -> Result<GeoBits, GeoError> {
+        if precision == 0 || precision > 32 {
+            return Err(GeoError::InvalidPrecision(precision));
+        }
+        for index in [lat_idx, lng_idx] {
+            if index as u64 >= 1u64 << precision {
+                return Err(GeoError::InvalidGridIndex(index));
+            }
         }
+        Ok(GeoBits {
+            bits: interleave64(lat_idx, lng_idx),
+            precision,
+        })
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Returns the `(lat_index, lng_index)` of this cell in the
+    /// `2^precision` by `2^precision` grid, counted from the south-west.
+    pub fn grid_index(&self) -> (u32, u32) {
+        let (lng, lat) = deinterleave64(self.bits);
+        (lat, lng)
+    }
 
-    #[test]
-    fn encode() {
-        let coord = Coord {
-            latitude: 25.006,
-            longitude: 121.46,
-        };
-        let hash = GeoBits::from(&coord, 15);
-        assert_eq!(hash.bits, 0b111001100010110101100011101010);
-        assert_eq!(hash.precision, 15);
+    /// Returns the latitude range of this cell, decoding only the latitude
+    /// bits.
+    pub fn latitude_range(&self) -> Range<f32> {
+        index_range(deinterleave_lat(self.bits), self.precision, &LAT_RNG)
     }
 
-    #[test]
-    fn decode() {
-        let area: Area = GeoBits {
-            bits: 0b111001100010110101100011101010,
-            precision: 15,
+    /// Returns the longitude range of this cell, decoding only the longitude
+    /// bits.
+    pub fn longitude_range(&self) -> Range<f32> {
+        index_range(deinterleave_lng(self.bits), self.precision, &LNG_RNG)
+    }
+
+    /// Returns the bits left-aligned in a u64, so that sorting by this key
+    /// gives Z-order (Morton order) across precisions.
+    pub fn morton_code(&self) -> u64 {
+        self.bits << (64 - self.precision as u32 * 2)
+    }
+
+    /// Returns the Morton code with its `precision * 2` significant bits in
+    /// reverse order, still left-aligned. Neighboring cells then differ in the
+    /// high bits, spreading them across the key space instead of clustering.
+    pub fn bit_reversed_key(&self) -> u64 {
+        self.bits.reverse_bits()
+    }
+
+    /// Returns how many leading significant bits the two Morton codes share,
+    /// at most the smaller of the two bit counts. More shared bits means a
+    /// smaller common ancestor and so, usually, closer cells. Z-order has
+    /// seams, though: neighbors on either side of the equator share only the
+    /// leading longitude bit, and neighbors on either side of the prime
+    /// meridian share none, so a low count does not prove the cells are far
+    /// apart.
+    pub fn shared_prefix_bits(&self, other: &GeoBits) -> u8 {
+        let max = self.precision.min(other.precision) as u32 * 2;
+        (self.morton_code() ^ other.morton_code())
+            .leading_zeros()
+            .min(max) as u8
+    }
+
+    /// Returns the reflected Gray code of this cell's position in Morton
+    /// order, left-aligned like `morton_code`. Cells adjacent in Morton order
+    /// get keys differing in exactly one bit.
+    pub fn gray_key(&self) -> u64 {
+        (self.bits ^ (self.bits >> 1)) << (64 - self.precision as u32 * 2)
+    }
+
+    /// Returns the cell at `precision` whose `gray_key` is `key`.
+    pub fn from_gray_key(key: u64, precision: u8) -> Result<GeoBits, GeoError> {
+        if precision == 0 || precision > 32 {
+            return Err(GeoError::InvalidPrecision(precision));
         }
-        .into();
-        assert!(area.contains(&Coord {
-            latitude: 25.006,
-            longitude: 121.46,
-        }));
+        let mut bits = key >> (64 - precision as u32 * 2);
+        let mut shift = 1;
+        while shift < 64 {
+            bits ^= bits >> shift;
+            shift <<= 1;
+        }
+        Ok(GeoBits { bits, precision })
     }
 
-    #[test]
-    fn next() {
-        let hash = GeoBits {
-            bits: 0b111001100010110101100011101010,
-            precision: 15,
-        };
-        assert_eq!(
-            hash.next_leftbottom(),
-            GeoBits {
-                bits: 0b11100110001011010110001110101000,
-                precision: 16,
-            }
-        );
-        assert_eq!(
-            hash.next_lefttop(),
-            GeoBits {
-                bits: 0b11100110001011010110001110101001,
-                precision: 16,
-            }
-        );
-        assert_eq!(
-            hash.next_rightbottom(),
-            GeoBits {
-                bits: 0b11100110001011010110001110101010,
-                precision: 16,
-            }
-        );
-        assert_eq!(
-            hash.next_righttop(),
-            GeoBits {
-                bits: 0b11100110001011010110001110101011,
-                precision: 16,
-            }
-        );
+    /// Returns a prefix-free key whose byte-wise order is Morton order, with
+    /// ancestors before their descendants: the left-aligned Morton code in
+    /// 7-bit groups, each byte with its high bit set, then the precision byte.
+    /// Left alignment keeps a coarse cell's high bits in the same bytes as a
+    /// fine cell's. The precision tells apart a cell and its south-west
+    /// descendants, whose codes agree, and sorts below any further group, so
+    /// a coarse key is never compared against a finer cell's bits.
+    pub fn to_sortable_bytes(&self) -> Vec<u8> {
+        let code = self.morton_code();
+        let mut bytes: Vec<u8> = (0..(self.precision as u32 * 2).div_ceil(7))
+            .map(|i| 0x80 | ((code << (i * 7)) >> 57) as u8)
+            .collect();
+        bytes.push(self.precision);
+        bytes
     }
 
-    #[test]
+    /// Returns the `precision * 2` significant bits as a zero-padded string of
+    /// '0' and '1', for debugging the interleaving.
+    pub fn to_bit_string(&self) -> String {
+        format!(
+            "{:0width$b}",
+            self.bits,
+            width = self.precision as usize * 2
+        )
+    }
+
+    /// Returns the cell one precision level up, or `None` at precision 1.
+    pub fn parent(&self) -> Option<GeoBits> {
+        if self.precision <= 1 {
+            return None;
+        }
+        Some(GeoBits {
+            bits: self.bits >> 2,
+            precision: self.precision - 1,
+        })
+    }
+
+    /// Returns the four cells one precision level down, in Morton order.
+    pub fn children(&self) -> [GeoBits; 4] {
+        if self.precision >= 32 {
+            panic!("Cannot subdivide a cell at precision 32");
+        }
+        [
+            self.next_leftbottom(),
+            self.next_lefttop(),
+            self.next_rightbottom(),
+            self.next_righttop(),
+        ]
+    }
+
+    /// Lazily yields the `4^(target_precision - precision)` cells at
+    /// `target_precision` inside this cell, in Morton order.
+    pub fn descendants(&self, target_precision: u8) -> impl Iterator<Item = GeoBits> {
+        if target_precision < self.precision || target_precision > 32 {
+            panic!("Target precision should satisfy precision <= target_precision <= 32");
+        }
+        let shift = (target_precision - self.precision) as u32 * 2;
+        let base = self.bits << shift;
+        (0..1u64 << shift).map(move |i| GeoBits {
+            bits: base | i,
+            precision: target_precision,
+        })
+    }
+
+    /// Returns how many meters of worst-case error rolling up to `parent()`
+    /// adds: the growth in the distance from a cell's center to its farthest
+    /// corner, i.e. half the growth in `Area::diagonal_meters`. Infinite at
+    /// precision 1, which has no parent.
+    pub fn rollup_error_m(&self) -> f32 {
+        let Some(parent) = self.parent() else {
+            return f32::INFINITY;
+        };
+        (Area::from(parent).diagonal_meters() - Area::from(*self).diagonal_meters()) / 2f32
+    }
+
+    /// Returns true if `other` is this cell or one of its descendants.
+    pub fn contains(&self, other: &GeoBits) -> bool {
+        other.precision >= self.precision
+            && other.bits >> ((other.precision - self.precision) as u32 * 2) == self.bits
+    }
+
+    /// Returns true if `coord` falls in this cell. Encodes `coord` and compares
+    /// bits, which avoids the float range checks of `Area::contains`.
+    pub fn covers(&self, coord: &Coord) -> bool {
+        GeoBits::from(coord, self.precision).bits == self.bits
+    }
+
+    /// Returns the finest cell containing both cells, or `None` if they only
+    /// share the whole world.
+    pub fn common_ancestor(&self, other: &GeoBits) -> Option<GeoBits> {
+        let precision = self.precision.min(other.precision) as u32;
+        let a = self.bits >> ((self.precision as u32 - precision) * 2);
+        let b = other.bits >> ((other.precision as u32 - precision) * 2);
+        let differing_pairs = (64 - (a ^ b).leading_zeros()).div_ceil(2);
+        let shared = precision - differing_pairs;
+        if shared == 0 {
+            return None;
+        }
+        Some(GeoBits {
+            bits: a >> (differing_pairs * 2),
+            precision: shared as u8,
+        })
+    }
+
+    /// Returns how many precision levels this cell sits below `ancestor`, or
+    /// `None` if `ancestor` does not contain it.
+    pub fn depth_below(&self, ancestor: &GeoBits) -> Option<u8> {
+        if ancestor.contains(self) {
+            Some(self.precision - ancestor.precision)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the other three children of this cell's parent, or `None` at
+    /// precision 1.
+    pub fn siblings(&self) -> Option<[GeoBits; 3]> {
+        let children = self.parent()?.children();
+        let mut siblings = children.iter().filter(|child| *child != self).copied();
+        Some([siblings.next()?, siblings.next()?, siblings.next()?])
+    }
+
+    /// Returns true if this cell touches the antimeridian (±180° longitude) or
+    /// a pole (±90° latitude).
+    pub fn is_edge_cell(&self) -> bool {
+        let (lng, lat) = deinterleave64(self.bits);
+        let max = ((1u64 << self.precision) - 1) as u32;
+        lat == 0 || lat == max || lng == 0 || lng == max
+    }
+
+    /// Returns the neighbor direction closest to the bearing from this cell's
+    /// center to `target`, measured on the lat/lng grid.
+    pub fn direction_toward(&self, target: &Coord) -> Direction {
+        let center = Area::from(*self).center();
+        let lat_diff = target.latitude - center.latitude;
+        // Go the short way around the antimeridian.
+        let mut lng_diff = target.longitude - center.longitude;
+        if lng_diff >= LNG_MAX {
+            lng_diff -= LNG_RNG.length();
+        } else if lng_diff < LNG_MIN {
+            lng_diff += LNG_RNG.length();
+        }
+        let angle = lat_diff.atan2(lng_diff).to_degrees();
+        match ((angle / 45f32).round() as i32).rem_euclid(8) {
+            0 => Direction::East,
+            1 => Direction::NorthEast,
+            2 => Direction::North,
+            3 => Direction::NorthWest,
+            4 => Direction::West,
+            5 => Direction::SouthWest,
+            6 => Direction::South,
+            _ => Direction::SouthEast,
+        }
+    }
+
+    /// Returns which side of this cell `coord` lies on, by comparing grid
+    /// rows and columns, or `None` if `coord` is inside the cell. Unlike
+    /// `direction_toward`, a point counts as north whenever it is in the same
+    /// column, however far it is. Longitude is compared the short way around.
+    pub fn direction_of(&self, coord: &Coord) -> Option<Direction> {
+        let (lat, lng) = self.grid_index();
+        let (target_lat, target_lng) = GeoBits::from(coord, self.precision).grid_index();
+        let size = 1i64 << self.precision;
+        let mut dx = (target_lng as i64 - lng as i64).rem_euclid(size);
+        if dx > size / 2 {
+            dx -= size;
+        }
+        let dy = target_lat as i64 - lat as i64;
+        match (dy.signum(), dx.signum()) {
+            (0, 0) => None,
+            (1, 0) => Some(Direction::North),
+            (1, 1) => Some(Direction::NorthEast),
+            (0, 1) => Some(Direction::East),
+            (-1, 1) => Some(Direction::SouthEast),
+            (-1, 0) => Some(Direction::South),
+            (-1, -1) => Some(Direction::SouthWest),
+            (0, -1) => Some(Direction::West),
+            _ => Some(Direction::NorthWest),
+        }
+    }
+
+    /// Returns a chain of cells from this cell to `dest`, both included, in
+    /// which each cell is one of the eight neighbors of the previous one. The
+    /// chain follows a Bresenham-style line over the grid and takes the short
+    /// way around the antimeridian. Returns `None` if the precisions differ.
+    pub fn path_to(&self, dest: &GeoBits) -> Option<Vec<GeoBits>> {
+        if dest.precision != self.precision {
+            return None;
+        }
+        let (lat, lng) = self.grid_index();
+        let (dest_lat, dest_lng) = dest.grid_index();
+        let size = 1i128 << self.precision;
+        let mut dx = (dest_lng as i128 - lng as i128).rem_euclid(size);
+        if dx > size / 2 {
+            dx -= size;
+        }
+        let dy = dest_lat as i128 - lat as i128;
+        let steps = dx.abs().max(dy.abs());
+        // Rounds `a / b` to the nearest integer, halves away from zero.
+        let div_round = |a: i128, b: i128| (2 * a + b * a.signum()) / (2 * b);
+        let path = (0..=steps)
+            .map(|i| {
+                let (y, x) = if steps == 0 {
+                    (0, 0)
+                } else {
+                    (div_round(dy * i, steps), div_round(dx * i, steps))
+                };
+                GeoBits {
+                    bits: interleave64(
+                        (lat as i128 + y) as u32,
+                        (lng as i128 + x).rem_euclid(size) as u32,
+                    ),
+                    precision: self.precision,
+                }
+            })
+            .collect();
+        Some(path)
+    }
+
+    /// Yields this cell, then the ring of cells at distance 1, then distance 2,
+    /// and so on until the whole grid has been visited. Longitude wraps around
+    /// the antimeridian; rows beyond the poles are skipped.
+    pub fn spiral(&self) -> impl Iterator<Item = GeoBits> {
+        let center = *self;
+        (0..=self.max_ring()).flat_map(move |k| {
+            let mut cells = Vec::new();
+            center.ring_into(k as i64, &mut cells);
+            cells
+        })
+    }
+
+    /// Returns every cell within Chebyshev distance `k` of this cell, in the
+    /// order `spiral` visits them.
+    pub fn k_ring(&self, k: u32) -> Vec<GeoBits> {
+        let mut cells = Vec::new();
+        self.k_ring_into(k, &mut cells);
+        cells
+    }
+
+    /// Like `k_ring`, but clears and fills `out` so one allocation can be
+    /// reused across many queries.
+    pub fn k_ring_into(&self, k: u32, out: &mut Vec<GeoBits>) {
+        out.clear();
+        for ring in 0..=(k as u64).min(self.max_ring()) {
+            self.ring_into(ring as i64, out);
+        }
+    }
+
+    // The distance of the farthest ring that still has cells in it.
+    fn max_ring(&self) -> u64 {
+        let (_, lat) = deinterleave64(self.bits);
+        let size = 1u64 << self.precision;
+        (lat as u64).max(size - 1 - lat as u64).max(size / 2)
+    }
+
+    // Appends the cells at Chebyshev distance exactly `k` from this cell,
+    // north to south and west to east.
+    fn ring_into(&self, k: i64, out: &mut Vec<GeoBits>) {
+        let (lng, lat) = deinterleave64(self.bits);
+        let size = 1i64 << self.precision;
+        // Longitude offsets are limited to (-half, half] so that wrapping never
+        // yields the same column twice.
+        let half = size / 2;
+        for dy in (-k..=k).rev() {
+            let y = lat as i64 + dy;
+            if y < 0 || y >= size {
+                continue;
+            }
+            let push = |dx: i64| {
+                let x = (lng as i64 + dx).rem_euclid(size);
+                out.push(GeoBits {
+                    bits: interleave64(y as u32, x as u32),
+                    precision: self.precision,
+                });
+            };
+            if dy.abs() == k {
+                ((-k).max(1 - half)..=k.min(half)).for_each(push);
+            } else {
+                [-k, k]
+                    .into_iter()
+                    .filter(|dx| *dx > -half && *dx <= half)
+                    .for_each(push);
+            }
+        }
+    }
+}
+
+// Returns the fractional `(lat, lng)` grid position of `coord` at `precision`.
+// Since precision can be 32, we need u64, and the arithmetic is done in f64
+// because f32 cannot resolve cells that fine.
+fn grid_position(coord: &Coord, precision: u8) -> (f64, f64) {
+    let scale = (1u64 << precision) as f64;
+    let lat = (coord.latitude as f64 - LAT_MIN as f64) * scale / LAT_RNG.length() as f64;
+    let lng = (coord.longitude as f64 - LNG_MIN as f64) * scale / LNG_RNG.length() as f64;
+    (lat, lng)
+}
+
+// Returns the `parts + 1` boundaries splitting `range` evenly, ending exactly at
+// `range.end`.
+fn split_range(range: &Range<f32>, parts: u32) -> Vec<f32> {
+    (0..=parts)
+        .map(|i| {
+            if i == parts {
+                range.end
+            } else {
+                range.start + range.length() * i as f32 / parts as f32
+            }
+        })
+        .collect()
+}
+
+/// Returns the `(height, width)` in meters of a cell at `precision` on the
+/// equator, from a table computed on first use.
+pub fn equatorial_cell_size(precision: u8) -> (f32, f32) {
+    static SIZES: OnceLock<[(f32, f32); 32]> = OnceLock::new();
+    if precision == 0 || precision > 32 {
+        panic!("Precision should satisfy 1 <= precision <= 32");
+    }
+    let sizes = SIZES.get_or_init(|| {
+        std::array::from_fn(|i| {
+            let cells = (1u64 << (i + 1)) as f64;
+            let radius = EARTH_RADIUS_M as f64;
+            let height = radius * (LAT_RNG.length() as f64).to_radians() / cells;
+            let width = radius * (LNG_RNG.length() as f64).to_radians() / cells;
+            (height as f32, width as f32)
+        })
+    });
+    sizes[precision as usize - 1]
+}
+
+// Average H3 cell area in square kilometers for resolutions 0 to 15, from the
+// H3 documentation.
+const H3_AREAS_KM2: [f64; 16] = [
+    4_357_449.416078381,
+    609_788.441794133,
+    86_801.780398997,
+    12_393.434655088,
+    1_770.347654491,
+    252.903858182,
+    36.129062164,
+    5.161293360,
+    0.737327598,
+    0.105332513,
+    0.015047502,
+    0.002149643,
+    0.000307092,
+    0.000043870,
+    0.000006267,
+    0.000000895,
+];
+
+// Natural log of the average cell area in square kilometers at `precision`.
+fn ln_average_area_km2(precision: u8) -> f64 {
+    let radius_km = EARTH_RADIUS_M as f64 / 1000.0;
+    (4.0 * std::f64::consts::PI * radius_km * radius_km).ln() - precision as f64 * 4f64.ln()
+}
+
+/// Returns the H3 resolution whose average cell area is closest to that of a
+/// cell at `precision`. This is only an approximation for reasoning across
+/// systems: H3 cells are hexagons of near-equal area, while geohash cells are
+/// rectangles that shrink toward the poles.
+pub fn h3_resolution_hint(precision: u8) -> u8 {
+    if precision == 0 || precision > 32 {
+        panic!("Precision should satisfy 1 <= precision <= 32");
+    }
+    let target = ln_average_area_km2(precision);
+    (0..H3_AREAS_KM2.len())
+        .min_by(|a, b| {
+            let da = (H3_AREAS_KM2[*a].ln() - target).abs();
+            let db = (H3_AREAS_KM2[*b].ln() - target).abs();
+            da.total_cmp(&db)
+        })
+        .unwrap() as u8
+}
+
+/// Returns the precision whose average cell area is closest to that of an H3
+/// cell at `resolution`, the approximate inverse of `h3_resolution_hint`.
+pub fn precision_for_h3_resolution(resolution: u8) -> u8 {
+    if resolution as usize >= H3_AREAS_KM2.len() {
+        panic!("Resolution should satisfy 0 <= resolution <= 15");
+    }
+    let target = H3_AREAS_KM2[resolution as usize].ln();
+    (1..=32)
+        .min_by(|a, b| {
+            let da = (ln_average_area_km2(*a) - target).abs();
+            let db = (ln_average_area_km2(*b) - target).abs();
+            da.total_cmp(&db)
+        })
+        .unwrap()
+}
+
+/// Returns the left-aligned Morton code of `coord` at `precision`, the key to
+/// sort by when bulk-loading a spatial index.
+pub fn key(coord: &Coord, precision: u8) -> Result<u64, GeoError> {
+    if precision == 0 || precision > 32 {
+        return Err(GeoError::InvalidPrecision(precision));
+    }
+    Ok(GeoBits::from(coord, precision).morton_code())
+}
+
+/// Returns a coordinate distributed uniformly over the sphere, drawing two
+/// numbers in [0, 1) from `uniform`. Latitude goes through `asin` so that
+/// points do not bunch up at the poles as they would if latitude degrees were
+/// uniform.
+pub fn random_coord<F: FnMut() -> f64>(uniform: &mut F) -> Coord {
+    let latitude = (2f64 * uniform() - 1f64).asin().to_degrees() as f32;
+    let longitude = (uniform() * LNG_RNG.length() as f64 + LNG_MIN as f64) as f32;
+    // Rounding to f32 can land on the excluded upper bounds.
+    Coord {
+        latitude: latitude.min(LAT_MAX.next_down()),
+        longitude: longitude.min(LNG_MAX.next_down()),
+    }
+}
+
+/// Returns the latitude grid indices at `precision` of the rows that the band
+/// from `lat_min` to `lat_max` touches, as a half-open range. Latitudes
+/// outside [-90, 90] are clamped, and a band with `lat_min > lat_max` is
+/// empty.
+pub fn lat_band_cells(lat_min: f32, lat_max: f32, precision: u8) -> Range<u32> {
+    if precision == 0 || precision > 32 {
+        panic!("Precision should satisfy 1 <= precision <= 32");
+    }
+    if lat_min > lat_max {
+        return 0..0;
+    }
+    let last = ((1u64 << precision) - 1) as f64;
+    let row = |latitude: f32| {
+        let coord = Coord {
+            latitude: latitude.clamp(LAT_MIN, LAT_MAX),
+            longitude: 0f32,
+        };
+        grid_position(&coord, precision).0.min(last) as u32
+    };
+    row(lat_min)..row(lat_max) + 1
+}
+
+/// Returns the cells `from` and `to` fall in at `precision` when they differ,
+/// i.e. when moving from one to the other crosses a cell boundary.
+pub fn transition(from: &Coord, to: &Coord, precision: u8) -> Option<(GeoBits, GeoBits)> {
+    let old = GeoBits::from(from, precision);
+    let new = GeoBits::from(to, precision);
+    (old != new).then_some((old, new))
+}
+
+/// Returns the center of each cell, in order.
+pub fn decode_batch(cells: &[GeoBits]) -> Vec<Coord> {
+    cells
+        .iter()
+        .map(|cell| Area::from(*cell).center())
+        .collect()
+}
+
+/// Returns true if `a` and `b` fall in different cells at `precision`.
+pub fn distinguishes(a: &Coord, b: &Coord, precision: u8) -> bool {
+    GeoBits::from(a, precision) != GeoBits::from(b, precision)
+}
+
+/// Anything that can name a cell at a given precision, so generic code can
+/// take coordinates, tuples and geohash strings alike.
+pub trait IntoGeoBits {
+    fn into_geobits(self, precision: u8) -> Result<GeoBits, GeoError>;
+}
+
+impl IntoGeoBits for Coord {
+    fn into_geobits(self, precision: u8) -> Result<GeoBits, GeoError> {
+        if precision == 0 || precision > 32 {
+            return Err(GeoError::InvalidPrecision(precision));
+        }
+        Ok(GeoBits::from(&self, precision))
+    }
+}
+
+impl IntoGeoBits for (f32, f32) {
+    /// Treats the tuple as `(latitude, longitude)`.
+    fn into_geobits(self, precision: u8) -> Result<GeoBits, GeoError> {
+        GeoBits::try_from((self.0, self.1, precision))
+    }
+}
+
+impl TryFrom<(f32, f32)> for Coord {
+    type Error = GeoError;
+
+    /// Builds a coordinate from `(latitude, longitude)`, checking the same
+    /// ranges as `Coord::new` without panicking.
+    fn try_from((latitude, longitude): (f32, f32)) -> Result<Coord, GeoError> {
+        if !LAT_RNG.contains(&latitude) {
+            return Err(GeoError::InvalidLatitude(latitude));
+        }
+        if !LNG_RNG.contains(&longitude) {
+            return Err(GeoError::InvalidLongitude(longitude));
+        }
+        Ok(Coord {
+            latitude,
+            longitude,
+        })
+    }
+}
+
+impl TryFrom<(f32, f32, u8)> for GeoBits {
+    type Error = GeoError;
+
+    /// Encodes `(latitude, longitude, precision)`.
+    fn try_from((latitude, longitude, precision): (f32, f32, u8)) -> Result<GeoBits, GeoError> {
+        let coord = Coord::try_from((latitude, longitude))?;
+        if precision == 0 || precision > 32 {
+            return Err(GeoError::InvalidPrecision(precision));
+        }
+        Ok(GeoBits::from(&coord, precision))
+    }
+}
+
+impl From<GeoBits> for Area {
+    fn from(hash: GeoBits) -> Area {
+        let (lng, lat) = deinterleave64(hash.bits);
+
+        // Note that if we look at the latitude and longitude bits separately,
+        // each cell is +1 from the previous cell:
+        //
+        // |---------------|---------------|
+        //         0               1
+        // |-------|-------|-------|-------|
+        //     00      01      10      11
+        // |---|---|---|---|---|---|---|---|
+        //  000 001 010 011 100 101 110 111
+        //
+        // Thus, to get the upper bound of a geohash, you just need to +1 to the
+        // latitude bits and then convert the number back to floating point.
+        let lat_range = index_range(lat, hash.precision, &LAT_RNG);
+        let lng_range = index_range(lng, hash.precision, &LNG_RNG);
+        Area {
+            lat_range,
+            lng_range,
+        }
+    }
+}
+
+// Returns the part of `range` covered by cell `index` at `precision`. The
+// bounds are computed in f64 and rounded outward, since at high precisions a
+// cell is narrower than the gap between adjacent f32s and the range must still
+// contain every coordinate encoded into it.
+pub(crate) fn index_range(index: u32, precision: u8, range: &Range<f32>) -> Range<f32> {
+    let float_scale = (1u64 << precision) as f64;
+    let start = range.start as f64;
+    let length = range.length() as f64;
+    Range {
+        start: round_down(start + (index as f64 / float_scale) * length),
+        end: round_up(start + ((index as u64 + 1) as f64 / float_scale) * length),
+    }
+}
+
+fn round_down(x: f64) -> f32 {
+    let y = x as f32;
+    if y as f64 > x {
+        y.next_down()
+    } else {
+        y
+    }
+}
+
+fn round_up(x: f64) -> f32 {
+    let y = x as f32;
+    if (y as f64) < x {
+        y.next_up()
+    } else {
+        y
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bits::tests::next as next_random;
+
+    #[test]
+    fn encode() {
+        let coord = Coord {
+            latitude: 25.006,
+            longitude: 121.46,
+        };
+        let hash = GeoBits::from(&coord, 15);
+        assert_eq!(hash.bits, 0b111001100010110101100011101010);
+        assert_eq!(hash.precision, 15);
+    }
+
+    #[test]
+    fn decode() {
+        let area: Area = GeoBits {
+            bits: 0b111001100010110101100011101010,
+            precision: 15,
+        }
+        .into();
+        assert!(area.contains(&Coord {
+            latitude: 25.006,
+            longitude: 121.46,
+        }));
+    }
+
+    #[test]
+    fn next() {
+        let hash = GeoBits {
+            bits: 0b111001100010110101100011101010,
+            precision: 15,
+        };
+        assert_eq!(
+            hash.next_leftbottom(),
+            GeoBits {
+                bits: 0b11100110001011010110001110101000,
+                precision: 16,
+            }
+        );
+        assert_eq!(
+            hash.next_lefttop(),
+            GeoBits {
+                bits: 0b11100110001011010110001110101001,
+                precision: 16,
+            }
+        );
+        assert_eq!(
+            hash.next_rightbottom(),
+            GeoBits {
+                bits: 0b11100110001011010110001110101010,
+                precision: 16,
+            }
+        );
+        assert_eq!(
+            hash.next_righttop(),
+            GeoBits {
+                bits: 0b11100110001011010110001110101011,
+                precision: 16,
+            }
+        );
+    }
+
+    #[test]
     fn neighbor() {
         let hash = GeoBits {
             bits: 0b111001100010110101100011101010,
             precision: 15,
         };
-        assert_eq!(hash.get_neighbors(), Neighbors::new());
+        assert_eq!(
+            hash.get_neighbors(),
+            Neighbors::from([
+                (
+                    Direction::North,
+                    GeoBits {
+                        bits: 0b111001100010110101100011101011,
+                        precision: 15,
+                    }
+                ),
+                (
+                    Direction::East,
+                    GeoBits {
+                        bits: 0b111001100010110101101001000000,
+                        precision: 15,
+                    }
+                ),
+                (
+                    Direction::South,
+                    GeoBits {
+                        bits: 0b111001100010110101100010111111,
+                        precision: 15,
+                    }
+                ),
+                (
+                    Direction::West,
+                    GeoBits {
+                        bits: 0b111001100010110101100011101000,
+                        precision: 15,
+                    }
+                ),
+                (
+                    Direction::NorthEast,
+                    GeoBits {
+                        bits: 0b111001100010110101101001000001,
+                        precision: 15,
+                    }
+                ),
+                (
+                    Direction::SouthEast,
+                    GeoBits {
+                        bits: 0b111001100010110101101000010101,
+                        precision: 15,
+                    }
+                ),
+                (
+                    Direction::SouthWest,
+                    GeoBits {
+                        bits: 0b111001100010110101100010111101,
+                        precision: 15,
+                    }
+                ),
+                (
+                    Direction::NorthWest,
+                    GeoBits {
+                        bits: 0b111001100010110101100011101001,
+                        precision: 15,
+                    }
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn siblings() {
+        let hash = GeoBits {
+            bits: 0b111001100010110101100011101010,
+            precision: 15,
+        };
+        let siblings = hash.siblings().unwrap();
+        let mut family = vec![hash, siblings[0], siblings[1], siblings[2]];
+        family.sort_by_key(|cell| cell.bits);
+        assert_eq!(family, hash.parent().unwrap().children());
+
+        let root = GeoBits {
+            bits: 0b10,
+            precision: 1,
+        };
+        assert_eq!(root.parent(), None);
+        assert_eq!(root.siblings(), None);
+    }
+
+    #[test]
+    fn edge_cell() {
+        let antimeridian = GeoBits::from(&Coord::new(10.0, 179.99), 10);
+        assert!(antimeridian.is_edge_cell());
+        let pole = GeoBits::from(&Coord::new(-89.99, 20.0), 10);
+        assert!(pole.is_edge_cell());
+        let interior = GeoBits::from(&Coord::new(25.006, 121.46), 10);
+        assert!(!interior.is_edge_cell());
+    }
+
+    #[test]
+    fn interpolate() {
+        let a = Coord::new(0.0, 10.0);
+        let b = Coord::new(0.0, 50.0);
+        let mid = a.interpolate(&b, 0.5);
+        assert!(mid.latitude.abs() < 1e-4);
+        assert!((mid.longitude - 30.0).abs() < 1e-4);
+        assert!(a.interpolate(&b, 0.0).distance(&a) < 1e-4);
+        assert!(a.interpolate(&b, 1.0).distance(&b) < 1e-4);
+
+        // Latitude stays below 90 at the north pole.
+        let pole = Coord::from_unit_vector([0.0, 0.0, 1.0]);
+        assert_eq!(pole.latitude, LAT_MAX.next_down());
+        let over_pole = Coord::new(89.0, 0.0).midpoint(&Coord::new(89.0, -180.0));
+        assert!(over_pole.latitude < LAT_MAX);
+        assert!(GeoBits::from(&over_pole, 32).covers(&over_pole));
+    }
+
+    #[test]
+    fn direction_toward() {
+        let hash = GeoBits::from(&Coord::new(25.006, 121.46), 15);
+        assert_eq!(
+            hash.direction_toward(&Coord::new(26.0, 122.5)),
+            Direction::NorthEast
+        );
+        assert_eq!(
+            hash.direction_toward(&Coord::new(20.0, 121.46)),
+            Direction::South
+        );
+        let hash = GeoBits::from(&Coord::new(0.0, 179.9), 15);
+        assert_eq!(
+            hash.direction_toward(&Coord::new(0.0, -179.9)),
+            Direction::East
+        );
+    }
+
+    #[test]
+    fn spiral() {
+        let hash = GeoBits {
+            bits: 0b111001100010110101100011101010,
+            precision: 15,
+        };
+        let mut block: Vec<GeoBits> = hash.spiral().take(9).collect();
+        let mut expected: Vec<GeoBits> = hash.get_neighbors().into_values().collect();
+        expected.push(hash);
+        block.sort_by_key(|cell| cell.bits);
+        expected.sort_by_key(|cell| cell.bits);
+        assert_eq!(block, expected);
+
+        let mut all: Vec<u64> = GeoBits {
+            bits: 0,
+            precision: 2,
+        }
+        .spiral()
+        .map(|cell| cell.bits)
+        .collect();
+        all.sort();
+        assert_eq!(all, (0..16).collect::<Vec<u64>>());
+    }
+
+    #[test]
+    fn midpoint() {
+        let mid = Coord::new(10.0, 170.0).midpoint(&Coord::new(10.0, -170.0));
+        assert!(mid.longitude.abs() > 179.0);
+        assert!(mid.latitude > 10.0 && mid.latitude < 11.0);
+    }
+
+    #[test]
+    fn haversine() {
+        let taipei = Coord::new(25.033, 121.565);
+        let tokyo = Coord::new(35.6762, 139.6503);
+        let distance = taipei.haversine(&tokyo);
+        assert!((distance - 2_100_000.0).abs() < 20_000.0);
+        assert_eq!(taipei.haversine(&taipei), 0.0);
+    }
+
+    #[test]
+    fn boundary_rule() {
+        // (0, 0) is the corner shared by all four precision-1 cells.
+        let origin = Coord::new(0.0, 0.0);
+        assert_eq!(
+            GeoBits::from_with_rule(&origin, 1, BoundaryRule::TowardNorthEast),
+            GeoBits {
+                bits: 0b11,
+                precision: 1,
+            }
+        );
+        assert_eq!(
+            GeoBits::from_with_rule(&origin, 1, BoundaryRule::TowardSouthWest),
+            GeoBits {
+                bits: 0b00,
+                precision: 1,
+            }
+        );
+        assert_eq!(GeoBits::from(&origin, 1).bits, 0b11);
+
+        // Away from a boundary both rules agree.
+        let coord = Coord::new(25.006, 121.46);
+        assert_eq!(
+            GeoBits::from_with_rule(&coord, 15, BoundaryRule::TowardNorthEast),
+            GeoBits::from_with_rule(&coord, 15, BoundaryRule::TowardSouthWest)
+        );
+        // The south-west corner of the world has nothing further south-west.
+        let corner = Coord::new(-90.0, -180.0);
+        assert_eq!(
+            GeoBits::from_with_rule(&corner, 5, BoundaryRule::TowardSouthWest).bits,
+            0
+        );
+    }
+
+    #[test]
+    fn bit_string() {
+        let hash = GeoBits {
+            bits: 0b111001100010110101100011101010,
+            precision: 15,
+        };
+        assert_eq!(hash.to_bit_string(), "111001100010110101100011101010");
+        let hash = GeoBits {
+            bits: 0b101,
+            precision: 3,
+        };
+        assert_eq!(hash.to_bit_string(), "000101");
+        assert_eq!(hash.to_bit_string().len(), 6);
+    }
+
+    #[test]
+    fn area_m2() {
+        let world = Area {
+            lat_range: LAT_RNG,
+            lng_range: LNG_RNG,
+        };
+        let sphere = 4.0 * std::f32::consts::PI * EARTH_RADIUS_M * EARTH_RADIUS_M;
+        assert!((world.area_m2() / sphere - 1.0).abs() < 1e-4);
+
+        let equator: Area = GeoBits::from(&Coord::new(0.0, 0.0), 10).into();
+        let arctic: Area = GeoBits::from(&Coord::new(80.0, 0.0), 10).into();
+        assert!(arctic.area_m2() < equator.area_m2());
+    }
+
+    #[test]
+    fn distance_to() {
+        let area = Area {
+            lat_range: 10.0..11.0,
+            lng_range: 20.0..21.0,
+        };
+        assert_eq!(area.distance_to(&Coord::new(10.5, 20.5)), 0.0);
+
+        // One degree of latitude south of the southern edge.
+        let south = area.distance_to(&Coord::new(9.0, 20.5));
+        assert!((south - 111_195.0).abs() < 100.0);
+
+        let corner = Coord::new(12.0, 22.0);
+        let expected = corner.haversine(&Coord::new(11.0, 21.0));
+        assert!((area.distance_to(&corner) - expected).abs() < 1.0);
+
+        let wrapped = Area {
+            lat_range: 0.0..1.0,
+            lng_range: 179.0..180.0,
+        };
+        assert!(wrapped.distance_to(&Coord::new(0.5, -179.0)) < 112_000.0);
+    }
+
+    #[test]
+    fn circle_overlap_m2() {
+        let coord = Coord::new(25.006, 121.46);
+        let area: Area = GeoBits::from(&coord, 15).into();
+        assert_eq!(area.circle_overlap_m2(&coord, 10_000.0), area.area_m2());
+        assert_eq!(
+            area.circle_overlap_m2(&Coord::new(-25.0, 0.0), 10_000.0),
+            0.0
+        );
+
+        // A circle around a corner covers about a quarter of the cell.
+        let corner = Coord::new(area.lat_range.start, area.lng_range.start);
+        let side = corner.haversine(&Coord::new(area.lat_range.start, area.lng_range.end));
+        let quarter = area.circle_overlap_m2(&corner, side / 2.0);
+        let expected = std::f32::consts::PI * side * side / 16.0;
+        assert!((quarter / expected - 1.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn sample_grid() {
+        for precision in [1, 10, 20] {
+            let area: Area = GeoBits::from(&Coord::new(25.006, 121.46), precision).into();
+            let samples = area.sample_grid(4, 3);
+            assert_eq!(samples.len(), 12);
+            assert!(samples.iter().all(|coord| area.contains(coord)));
+            assert!(samples[0].latitude < samples[3].latitude);
+            assert!(samples[0].longitude < samples[1].longitude);
+        }
+    }
+
+    #[test]
+    fn overlap_fraction() {
+        let area = Area {
+            lat_range: 10.0..11.0,
+            lng_range: 20.0..21.0,
+        };
+        let apart = Area {
+            lat_range: -5.0..-4.0,
+            lng_range: 20.0..21.0,
+        };
+        assert_eq!(area.overlap_fraction(&apart), 0.0);
+        let around = Area {
+            lat_range: 0.0..20.0,
+            lng_range: 0.0..40.0,
+        };
+        assert_eq!(area.overlap_fraction(&around), 1.0);
+        let east_half = Area {
+            lat_range: 0.0..20.0,
+            lng_range: 20.5..40.0,
+        };
+        assert!((area.overlap_fraction(&east_half) - 0.5).abs() < 1e-4);
+        // The northern half of a cell is slightly smaller than the southern.
+        let north_half = Area {
+            lat_range: 10.5..20.0,
+            lng_range: 0.0..40.0,
+        };
+        let fraction = area.overlap_fraction(&north_half);
+        assert!(0.49 < fraction && fraction < 0.5);
+    }
+
+    #[test]
+    fn eq_at_precision() {
+        let area: Area = GeoBits::from(&Coord::new(25.006, 121.46), 20).into();
+        let noisy = Area {
+            lat_range: area.lat_range.start.next_up()..area.lat_range.end.next_down(),
+            lng_range: area.lng_range.start.next_down()..area.lng_range.end.next_up(),
+        };
+        assert_ne!(area, noisy);
+        assert!(area.eq_at_precision(&noisy, 20));
+        let east: Area = GeoBits::from(&Coord::new(25.006, 121.46), 20)
+            .get_neighbor(Direction::East)
+            .into();
+        assert!(!area.eq_at_precision(&east, 20));
+        assert!(area.eq_at_precision(&east, 10));
+    }
+
+    #[test]
+    fn to_microdegrees() {
+        let mut state = 29;
+        for _ in 0..100 {
+            let lat = (next_random(&mut state) % 180_000) as f32 / 1000.0 - 90.0;
+            let lng = (next_random(&mut state) % 360_000) as f32 / 1000.0 - 180.0;
+            let precision = (next_random(&mut state) % 32 + 1) as u8;
+            let area: Area = GeoBits::from(&Coord::new(lat, lng), precision).into();
+            let (south, west, north, east) = area.to_microdegrees();
+            for (micro, degrees) in [
+                (south, area.lat_range.start),
+                (west, area.lng_range.start),
+                (north, area.lat_range.end),
+                (east, area.lng_range.end),
+            ] {
+                assert!((micro as f64 / 1e6 - degrees as f64).abs() <= 1e-6);
+            }
+        }
+        let world: Area = GeoBits::from(&Coord::new(10.0, 10.0), 1).into();
+        assert_eq!(world.to_microdegrees(), (0, 0, 90_000_000, 180_000_000));
+    }
+
+    #[test]
+    fn diagonal_meters() {
+        let coord = Coord::new(25.006, 121.46);
+        let coarse: Area = GeoBits::from(&coord, 10).into();
+        let fine: Area = GeoBits::from(&coord, 11).into();
+        let ratio = coarse.diagonal_meters() / fine.diagonal_meters();
+        assert!((ratio - 2.0).abs() < 0.01);
+        // A precision 10 cell is about 39 km across at this latitude.
+        assert!((30_000.0..50_000.0).contains(&coarse.diagonal_meters()));
+    }
+
+    #[test]
+    fn precision_constants() {
+        for precision in [PRECISION_CITY, PRECISION_STREET, PRECISION_BUILDING] {
+            assert!((1..=32).contains(&precision));
+        }
+        const { assert!(PRECISION_CITY < PRECISION_STREET) };
+        const { assert!(PRECISION_STREET < PRECISION_BUILDING) };
+
+        let street: Area = GeoBits::from(&Coord::new(0.0, 0.0), PRECISION_STREET).into();
+        let height = Coord::new(street.lat_range.start, 0.0)
+            .haversine(&Coord::new(street.lat_range.end, 0.0));
+        assert!((height - 76.0).abs() < 1.0);
+    }
+
+    #[test]
+    fn to_coord() {
+        // The north-east quarter of the world.
+        let hash = GeoBits {
+            bits: 0b11,
+            precision: 1,
+        };
+        assert_eq!(hash.to_coord(Anchor::Center), Coord::new(45.0, 90.0));
+        assert_eq!(hash.to_coord(Anchor::SouthWest), Coord::new(0.0, 0.0));
+        assert_eq!(
+            hash.to_coord(Anchor::NorthEast),
+            Coord {
+                latitude: 90.0,
+                longitude: 180.0,
+            }
+        );
+    }
+
+    #[test]
+    fn neighbor_at_max_precision() {
+        let max = u32::MAX;
+        let cell = |lat: u32, lng: u32| GeoBits {
+            bits: interleave64(lat, lng),
+            precision: 32,
+        };
+
+        let north_east = cell(max, max);
+        assert_eq!(north_east.get_neighbor(Direction::East), cell(max, 0));
+        assert_eq!(north_east.get_neighbor(Direction::North), cell(0, max));
+        assert_eq!(north_east.get_neighbor(Direction::NorthEast), cell(0, 0));
+        assert_eq!(
+            north_east.get_neighbor(Direction::SouthWest),
+            cell(max - 1, max - 1)
+        );
+
+        let south_west = cell(0, 0);
+        assert_eq!(south_west.get_neighbor(Direction::West), cell(0, max));
+        assert_eq!(south_west.get_neighbor(Direction::South), cell(max, 0));
+        assert_eq!(south_west.get_neighbor(Direction::NorthEast), cell(1, 1));
+
+        let area: Area = north_east.into();
+        assert_eq!(area.lat_range.end, LAT_MAX);
+        assert_eq!(area.lng_range.end, LNG_MAX);
+    }
+
+    #[test]
+    fn offset() {
+        let hash = GeoBits {
+            bits: 0b111001100010110101100011101010,
+            precision: 15,
+        };
+        let mut stepped = hash;
+        stepped.move_x(false).move_x(false).move_x(false);
+        assert_eq!(hash.offset(3, 0), Some(stepped));
+
+        let mut stepped = hash;
+        stepped.move_y(true).move_y(true).move_x(true);
+        assert_eq!(hash.offset(-1, -2), Some(stepped));
+        assert_eq!(hash.offset(0, 0), Some(hash));
+
+        // Longitude wraps, latitude does not.
+        let east_edge = GeoBits::from(&Coord::new(0.0, 179.9), 4);
+        assert_eq!(
+            east_edge.offset(1, 0),
+            Some(GeoBits::from(&Coord::new(0.0, -179.9), 4))
+        );
+        assert_eq!(east_edge.offset(0, 8), None);
+        assert_eq!(east_edge.offset(0, -9), None);
+        assert_eq!(east_edge.offset(0, i64::MAX), None);
+        assert_eq!(east_edge.offset(0, i64::MIN), None);
+        assert_eq!(east_edge.offset(i64::MAX, 0), east_edge.offset(-1, 0));
+        assert_eq!(east_edge.offset(i64::MIN, 0), Some(east_edge));
+    }
+
+    #[test]
+    fn distinguishes() {
+        let a = Coord::new(25.006, 121.46);
+        let b = Coord::new(25.0061, 121.4601);
+        assert!(!super::distinguishes(&a, &b, 10));
+        assert!(super::distinguishes(&a, &b, 25));
+        assert!(!super::distinguishes(&a, &a, 32));
+    }
+
+    #[test]
+    fn subdivide() {
+        let area: Area = GeoBits::from(&Coord::new(25.006, 121.46), 10).into();
+        let parts = area.subdivide(2, 2);
+        assert_eq!(parts.len(), 4);
+        assert_eq!(parts[0].lat_range.start, area.lat_range.start);
+        assert_eq!(parts[0].lng_range.start, area.lng_range.start);
+        assert_eq!(parts[3].lat_range.end, area.lat_range.end);
+        assert_eq!(parts[3].lng_range.end, area.lng_range.end);
+        assert_eq!(parts[0].lat_range.end, parts[2].lat_range.start);
+        assert_eq!(parts[0].lng_range.end, parts[1].lng_range.start);
+        let total: f32 = parts.iter().map(Area::area_m2).sum();
+        assert!((total / area.area_m2() - 1.0).abs() < 1e-4);
+
+        // A 2x2 split of a cell is exactly its children.
+        let children: Vec<Area> = GeoBits::from(&Coord::new(25.006, 121.46), 10)
+            .children()
+            .iter()
+            .map(|child| Area::from(*child))
+            .collect();
+        for child in &children {
+            assert!(parts.contains(child));
+        }
+    }
+
+    #[test]
+    fn encode_decode_round_trip() {
+        // Seeded random coordinates, plus the corners of the valid range.
+        // Without a proptest dependency there is no shrinking, so a failure
+        // prints its input instead.
+        let corners = [
+            (LAT_MIN, LNG_MIN),
+            (LAT_MIN, LNG_MAX.next_down()),
+            (LAT_MAX.next_down(), LNG_MIN),
+            (LAT_MAX.next_down(), LNG_MAX.next_down()),
+        ];
+        for (latitude, longitude) in corners {
+            let coord = Coord::new(latitude, longitude);
+            for precision in 1..=32 {
+                let area: Area = GeoBits::from(&coord, precision).into();
+                assert!(
+                    area.contains(&coord),
+                    "{:?} at precision {}",
+                    coord,
+                    precision
+                );
+            }
+        }
+
+        let mut state = 0xC0FFEE;
+        let unit = |state: &mut u64| (next_random(state) >> 11) as f64 / (1u64 << 53) as f64;
+        for _ in 0..10_000 {
+            let latitude = (LAT_MIN as f64 + unit(&mut state) * 180f64) as f32;
+            let longitude = (LNG_MIN as f64 + unit(&mut state) * 360f64) as f32;
+            let precision = (next_random(&mut state) % 32 + 1) as u8;
+            let coord = Coord::new(
+                latitude.min(LAT_MAX.next_down()),
+                longitude.min(LNG_MAX.next_down()),
+            );
+            let area: Area = GeoBits::from(&coord, precision).into();
+            assert!(
+                area.contains(&coord),
+                "{:?} at precision {} decoded to {:?}",
+                coord,
+                precision,
+                area
+            );
+        }
+    }
+
+    #[test]
+    fn antipode() {
+        let hash = GeoBits::from(&Coord::new(25.006, 121.46), 15);
+        let antipode = hash.antipode();
+        assert_eq!(antipode.antipode(), hash);
+        let distance = Area::from(hash)
+            .center()
+            .haversine(&Area::from(antipode).center());
+        assert!((distance - 20_015_000.0).abs() < 10_000.0);
+        assert!(Area::from(antipode).contains(&Coord::new(-25.006, -58.54)));
+    }
+
+    #[test]
+    fn neighbors_clockwise() {
+        let hash = GeoBits {
+            bits: 0b111001100010110101100011101010,
+            precision: 15,
+        };
+        let neighbors = hash.neighbors_clockwise();
+        let directions = [
+            Direction::North,
+            Direction::NorthEast,
+            Direction::East,
+            Direction::SouthEast,
+            Direction::South,
+            Direction::SouthWest,
+            Direction::West,
+            Direction::NorthWest,
+        ];
+        for (neighbor, direction) in neighbors.iter().zip(directions) {
+            assert_eq!(*neighbor, hash.get_neighbor(direction));
+        }
+    }
+
+    #[test]
+    fn depth_below() {
+        let hash = GeoBits::from(&Coord::new(25.006, 121.46), 15);
+        let grandparent = hash.parent().unwrap().parent().unwrap();
+        assert!(grandparent.contains(&hash));
+        assert!(!hash.contains(&grandparent));
+        assert_eq!(hash.depth_below(&grandparent), Some(2));
+        assert_eq!(hash.depth_below(&hash), Some(0));
+
+        let unrelated = GeoBits::from(&Coord::new(-33.87, 151.21), 13);
+        assert_eq!(hash.depth_below(&unrelated), None);
+    }
+
+    #[test]
+    fn key() {
+        let coord = Coord::new(25.006, 121.46);
+        for precision in [1, 15, 32] {
+            assert_eq!(
+                super::key(&coord, precision),
+                Ok(GeoBits::from(&coord, precision).morton_code())
+            );
+        }
+        assert_eq!(
+            super::key(&coord, 15),
+            Ok(0b111001100010110101100011101010 << 34)
+        );
+        assert_eq!(super::key(&coord, 0), Err(GeoError::InvalidPrecision(0)));
+        assert_eq!(super::key(&coord, 33), Err(GeoError::InvalidPrecision(33)));
+    }
+
+    #[test]
+    fn axis_ranges() {
+        for precision in [1, 15, 32] {
+            let hash = GeoBits::from(&Coord::new(25.006, 121.46), precision);
+            let area: Area = hash.into();
+            assert_eq!(hash.latitude_range(), area.lat_range);
+            assert_eq!(hash.longitude_range(), area.lng_range);
+        }
+    }
+
+    #[test]
+    fn clamp_to_world() {
+        let area = Area {
+            lat_range: 85.0..95.0,
+            lng_range: 170.0..190.0,
+        };
+        assert_eq!(
+            area.clamp_to_world(),
+            Area {
+                lat_range: 85.0..90.0,
+                lng_range: 170.0..180.0,
+            }
+        );
+        let inside: Area = GeoBits::from(&Coord::new(25.006, 121.46), 15).into();
+        assert_eq!(inside.clamp_to_world(), inside);
+    }
+
+    #[test]
+    fn try_from_bbox() {
+        let hash = GeoBits::from(&Coord::new(25.006, 121.46), 15);
+        let sw = hash.to_coord(Anchor::SouthWest);
+        let ne = hash.to_coord(Anchor::NorthEast);
+        assert_eq!(GeoBits::try_from_bbox(&sw, &ne), Some(hash));
+
+        let world = GeoBits::try_from_bbox(&Coord::new(-90.0, -180.0), &Coord::new(0.0, 0.0));
+        assert_eq!(
+            world,
+            Some(GeoBits {
+                bits: 0,
+                precision: 1
+            })
+        );
+
+        assert_eq!(
+            GeoBits::try_from_bbox(&Coord::new(10.0, 20.0), &Coord::new(12.0, 23.0)),
+            None
+        );
+        assert_eq!(GeoBits::try_from_bbox(&ne, &sw), None);
+    }
+
+    #[test]
+    fn grid_index() {
+        let hash = GeoBits::from(&Coord::new(25.006, 121.46), 15);
+        let (lat, lng) = hash.grid_index();
+        assert_eq!((lat, lng), (20936, 27439));
+        assert_eq!(
+            hash.get_neighbor(Direction::North).grid_index(),
+            (lat + 1, lng)
+        );
+        assert_eq!(
+            hash.get_neighbor(Direction::East).grid_index(),
+            (lat, lng + 1)
+        );
+        assert_eq!(
+            hash.get_neighbor(Direction::South).grid_index(),
+            (lat - 1, lng)
+        );
+        assert_eq!(
+            hash.get_neighbor(Direction::West).grid_index(),
+            (lat, lng - 1)
+        );
+    }
+
+    #[test]
+    fn from_grid_index() {
+        for precision in [1, 15, 32] {
+            let g = GeoBits::from(&Coord::new(25.006, 121.46), precision);
+            let (lat, lng) = g.grid_index();
+            assert_eq!(GeoBits::from_grid_index(lat, lng, g.precision()), Ok(g));
+        }
+        assert_eq!(
+            GeoBits::from_grid_index(4, 0, 2),
+            Err(GeoError::InvalidGridIndex(4))
+        );
+        assert_eq!(
+            GeoBits::from_grid_index(0, 0, 0),
+            Err(GeoError::InvalidPrecision(0))
+        );
+    }
+
+    #[test]
+    fn equatorial_cell_size() {
+        for precision in [1, 10, 20, 32] {
+            let (height, width) = super::equatorial_cell_size(precision);
+            let hash = GeoBits::from(&Coord::new(0.0, 0.0), precision);
+            let sw = hash.to_coord(Anchor::SouthWest);
+            let ne = hash.to_coord(Anchor::NorthEast);
+            let measured_height = sw.haversine(&Coord {
+                latitude: ne.latitude,
+                longitude: sw.longitude,
+            });
+            let measured_width = sw.haversine(&Coord {
+                latitude: sw.latitude,
+                longitude: ne.longitude,
+            });
+            assert!((height / measured_height - 1.0).abs() < 1e-3);
+            assert!((width / measured_width - 1.0).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn common_ancestor() {
+        let hash = GeoBits::from(&Coord::new(25.006, 121.46), 15);
+        let grandparent = hash.parent().unwrap().parent().unwrap();
+        let cousin = grandparent.children()[3].children()[0];
+        assert_eq!(hash.common_ancestor(&hash), Some(hash));
+        assert_eq!(hash.common_ancestor(&grandparent), Some(grandparent));
+        assert_eq!(cousin.common_ancestor(&hash), Some(grandparent));
+
+        let south = GeoBits::from(&Coord::new(-25.0, 121.46), 15);
+        assert_eq!(hash.common_ancestor(&south), None);
+    }
+
+    #[test]
+    fn distance_to_self() {
+        for coord in [
+            Coord::new(25.006, 121.46),
+            Coord::new(-89.99999, -179.99998),
+            Coord::new(0.0, 0.0),
+        ] {
+            let copy = coord;
+            assert_eq!(coord.distance(&copy), 0.0);
+            assert_eq!(coord.haversine(&copy), 0.0);
+        }
+        let antipodal = Coord::new(45.0, 0.0).haversine(&Coord::new(-45.0, -180.0));
+        assert!(!antipodal.is_nan());
+    }
+
+    #[test]
+    fn neighbors_along_edge() {
+        let hash = GeoBits::from(&Coord::new(25.006, 121.46), 5);
+        let east = hash.neighbors_along_edge(Direction::East, 7);
+        let expected: Vec<GeoBits> = hash
+            .get_neighbor(Direction::East)
+            .children()
+            .iter()
+            .flat_map(|child| child.children())
+            .filter(|cell| cell.grid_index().1 == east[0].grid_index().1)
+            .collect();
+        assert_eq!(east.len(), 4);
+        let mut sorted = east.clone();
+        sorted.sort_by_key(|cell| cell.grid_index());
+        assert_eq!(east, sorted);
+        for cell in &expected {
+            assert!(east.contains(cell));
+        }
+        // The west-most column of the eastern neighbor.
+        assert_eq!(east[0].grid_index().1, (hash.grid_index().1 + 1) << 2);
+
+        assert_eq!(
+            hash.neighbors_along_edge(Direction::East, 5),
+            vec![hash.get_neighbor(Direction::East)]
+        );
+        let corner = hash.neighbors_along_edge(Direction::NorthWest, 7);
+        assert_eq!(corner.len(), 1);
+        let (lat, lng) = hash.grid_index();
+        assert_eq!(corner[0].grid_index(), (((lat + 1) << 2), (lng << 2) - 1));
+
+        let top = GeoBits::from(&Coord::new(89.0, 0.0), 5);
+        assert!(top.neighbors_along_edge(Direction::North, 7).is_empty());
+    }
+
+    #[test]
+    fn sortable_bytes() {
+        let hash = GeoBits::from(&Coord::new(25.006, 121.46), 5);
+        assert_eq!(hash.to_sortable_bytes().len(), 3);
+        assert_eq!(
+            GeoBits::from(&Coord::new(25.006, 121.46), 32)
+                .to_sortable_bytes()
+                .len(),
+            11
+        );
+
+        // A cell and its south-west descendant share a code but not a key.
+        let coarse = GeoBits {
+            bits: 0b10,
+            precision: 1,
+        };
+        let fine = GeoBits {
+            bits: 0b1000,
+            precision: 2,
+        };
+        assert!(coarse.to_sortable_bytes() < fine.to_sortable_bytes());
+        // The precision byte sorts below whatever bits follow.
+        let deep = GeoBits {
+            bits: 0b10 << 14 | 1,
+            precision: 8,
+        };
+        assert!(coarse.to_sortable_bytes() < deep.to_sortable_bytes());
+
+        let mut state = 7;
+        let mut cells: Vec<GeoBits> = (0..200)
+            .map(|_| {
+                let lat = (next_random(&mut state) % 180_000) as f32 / 1000.0 - 90.0;
+                let lng = (next_random(&mut state) % 360_000) as f32 / 1000.0 - 180.0;
+                let precision = (next_random(&mut state) % 32 + 1) as u8;
+                GeoBits::from(&Coord::new(lat, lng), precision)
+            })
+            .collect();
+        let mut by_bytes = cells.clone();
+        by_bytes.sort_by_key(|cell| cell.to_sortable_bytes());
+        cells.sort_by_key(|cell| (cell.morton_code(), cell.precision));
+        assert_eq!(by_bytes, cells);
+        // Coarse and fine cells in the same place, where keys would collide.
+        let mut nested: Vec<GeoBits> = (1..=32)
+            .map(|precision| GeoBits::from(&Coord::new(-90.0, -180.0), precision))
+            .collect();
+        nested.reverse();
+        nested.sort_by_key(|cell| cell.to_sortable_bytes());
+        assert!(nested
+            .windows(2)
+            .all(|pair| pair[0].precision < pair[1].precision));
+    }
+
+    #[test]
+    fn encode_fixed() {
+        assert_eq!(
+            GeoBits::encode_fixed(25_006_000, 121_460_000, 20),
+            GeoBits::from(&Coord::new(25.006, 121.46), 20)
+        );
+
+        // Multiples of 1/64 degree are exact in f32, so the float encoder has
+        // no rounding to disagree about, including on cell boundaries.
+        let mut state = 11;
+        for _ in 0..1000 {
+            let lat = (next_random(&mut state) % 11_520) as i32 - 5_760;
+            let lng = (next_random(&mut state) % 23_040) as i32 - 11_520;
+            let precision = (next_random(&mut state) % 32 + 1) as u8;
+            let coord = Coord::new(lat as f32 / 64.0, lng as f32 / 64.0);
+            assert_eq!(
+                GeoBits::encode_fixed(lat * 15_625, lng * 15_625, precision),
+                GeoBits::from(&coord, precision)
+            );
+        }
+    }
+
+    #[test]
+    fn k_ring() {
+        let hash = GeoBits::from(&Coord::new(25.006, 121.46), 15);
+        let ring = hash.k_ring(2);
+        assert_eq!(ring.len(), 25);
+        assert_eq!(ring, hash.spiral().take(25).collect::<Vec<_>>());
+
+        let mut out = vec![hash; 100];
+        hash.k_ring_into(2, &mut out);
+        assert_eq!(out, ring);
+        hash.k_ring_into(0, &mut out);
+        assert_eq!(out, vec![hash]);
+
+        // Large rings stop once the whole grid is covered.
+        let coarse = GeoBits::from(&Coord::new(25.006, 121.46), 2);
+        assert_eq!(coarse.k_ring(u32::MAX).len(), 16);
+    }
+
+    #[test]
+    fn covers() {
+        let hash = GeoBits::from(&Coord::new(25.006, 121.46), 15);
+        assert!(hash.covers(&Area::from(hash).center()));
+        let east = hash.get_neighbor(Direction::East);
+        assert!(!hash.covers(&Area::from(east).center()));
+        // The south-west corner belongs to the cell, the north-east does not.
+        assert!(hash.covers(&hash.to_coord(Anchor::SouthWest)));
+        assert!(!hash.covers(&hash.to_coord(Anchor::NorthEast)));
+    }
+
+    #[test]
+    fn h3_resolution_hint() {
+        let hints: Vec<u8> = (1..=32).map(super::h3_resolution_hint).collect();
+        assert!(hints.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert_eq!(hints[0], 0);
+        assert_eq!(hints[31], 15);
+
+        let precisions: Vec<u8> = (0..=15).map(precision_for_h3_resolution).collect();
+        assert!(precisions.windows(2).all(|pair| pair[0] < pair[1]));
+        // H3 resolution 9 cells are about 0.1 km2, like precision 16 cells.
+        assert_eq!(precision_for_h3_resolution(9), 16);
+        for resolution in 0..=15 {
+            let hint = super::h3_resolution_hint(precision_for_h3_resolution(resolution));
+            assert!(hint.abs_diff(resolution) <= 1);
+        }
+    }
+
+    #[test]
+    fn neighbor_at() {
+        let hash = GeoBits::from(&Coord::new(25.006, 121.46), 5);
+        let north = hash.get_neighbor(Direction::North);
+        let fine = hash.neighbor_at(Direction::North, 9);
+        assert_eq!(fine.precision, 9);
+        assert!(north.contains(&fine));
+        assert!(fine.covers(&Area::from(north).center()));
+        assert_eq!(hash.neighbor_at(Direction::North, 5), north);
+        assert_eq!(
+            hash.neighbor_at(Direction::North, 3),
+            GeoBits::from(&Area::from(north).center(), 3)
+        );
+    }
+
+    #[test]
+    fn try_from_tuples() {
+        let coord: Coord = (25.006, 121.46).try_into().unwrap();
+        assert_eq!(coord, Coord::new(25.006, 121.46));
+        assert_eq!(
+            Coord::try_from((90.0, 0.0)),
+            Err(GeoError::InvalidLatitude(90.0))
+        );
+        assert!(Coord::try_from((0.0, f32::NAN)).is_err());
+
+        let hash: GeoBits = (25.006, 121.46, 15).try_into().unwrap();
+        assert_eq!(hash, GeoBits::from(&coord, 15));
+        assert_eq!(
+            GeoBits::try_from((25.006, 121.46, 33)),
+            Err(GeoError::InvalidPrecision(33))
+        );
+        assert_eq!(
+            GeoBits::try_from((25.006, -181.0, 15)),
+            Err(GeoError::InvalidLongitude(-181.0))
+        );
+    }
+
+    #[test]
+    fn bit_reversed_key() {
+        let hash = GeoBits {
+            bits: 0b100111,
+            precision: 3,
+        };
+        assert_eq!(hash.bit_reversed_key(), 0b111001 << 58);
+
+        let mut state = 13;
+        for _ in 0..100 {
+            let precision = (next_random(&mut state) % 32 + 1) as u8;
+            let hash = GeoBits {
+                bits: next_random(&mut state) >> (64 - precision as u32 * 2),
+                precision,
+            };
+            let reversed = GeoBits {
+                bits: hash.bit_reversed_key() >> (64 - precision as u32 * 2),
+                precision,
+            };
+            assert_eq!(reversed.bit_reversed_key(), hash.morton_code());
+        }
+    }
+
+    #[test]
+    fn neighbor_with_edge() {
+        let hash = GeoBits::from(&Coord::new(25.006, 121.46), 15);
+        let (east, edge) = hash.neighbor_with_edge(Direction::East);
+        assert_eq!(east, hash.get_neighbor(Direction::East));
+        let side = hash.to_coord(Anchor::SouthWest).haversine(&Coord::new(
+            hash.latitude_range().end,
+            hash.longitude_range().start,
+        ));
+        assert!((edge / side - 1.0).abs() < 1e-3);
+
+        let (_, north) = hash.neighbor_with_edge(Direction::North);
+        let (_, south) = hash.neighbor_with_edge(Direction::South);
+        assert!(north < south);
+        let top = Coord::new(hash.latitude_range().end, hash.longitude_range().start);
+        assert!((north / top.haversine(&hash.to_coord(Anchor::NorthEast)) - 1.0).abs() < 1e-3);
+        assert_eq!(hash.neighbor_with_edge(Direction::NorthEast).1, 0.0);
+    }
+
+    #[test]
+    fn descendants() {
+        let hash = GeoBits::from(&Coord::new(25.006, 121.46), 15);
+        assert_eq!(hash.descendants(16).collect::<Vec<_>>(), hash.children());
+        assert_eq!(hash.descendants(15).collect::<Vec<_>>(), vec![hash]);
+
+        let grandchildren: Vec<GeoBits> = hash.descendants(17).collect();
+        assert_eq!(grandchildren.len(), 16);
+        assert!(grandchildren
+            .windows(2)
+            .all(|pair| pair[0].morton_code() < pair[1].morton_code()));
+        assert!(grandchildren.iter().all(|cell| hash.contains(cell)));
+
+        // Lazy, so even a huge drill-down can be sampled.
+        let world = GeoBits::from(&Coord::new(0.0, 0.0), 1);
+        assert_eq!(world.descendants(32).nth(3).unwrap().precision, 32);
+    }
+
+    #[test]
+    fn from_dms() {
+        // Eiffel Tower: 48°51'29.6"N 2°17'40.2"E.
+        let coord = Coord::from_dms((48, 51, 29.6), (2, 17, 40.2)).unwrap();
+        assert!((coord.latitude - 48.858222).abs() < 1e-5);
+        assert!((coord.longitude - 2.2945).abs() < 1e-5);
+
+        // Sydney Opera House: 33°51'25"S 151°12'55"E.
+        let coord = Coord::from_dms((-33, 51, 25.0), (151, 12, 55.0)).unwrap();
+        assert!((coord.latitude + 33.856944).abs() < 1e-5);
+
+        assert!(matches!(
+            Coord::from_dms((10, 60, 0.0), (0, 0, 0.0)),
+            Err(GeoError::InvalidLatitude(_))
+        ));
+        assert!(matches!(
+            Coord::from_dms((10, 0, 0.0), (180, 0, 0.0)),
+            Err(GeoError::InvalidLongitude(_))
+        ));
+    }
+
+    #[test]
+    fn direction_of() {
+        let hash = GeoBits::from(&Coord::new(25.006, 121.46), 15);
+        let center = Area::from(hash).center();
+        assert_eq!(hash.direction_of(&center), None);
+        assert_eq!(
+            hash.direction_of(&Coord::new(40.0, center.longitude)),
+            Some(Direction::North)
+        );
+        assert_eq!(
+            hash.direction_of(&Coord::new(10.0, 130.0)),
+            Some(Direction::SouthEast)
+        );
+        let west = Area::from(hash.get_neighbor(Direction::West)).center();
+        assert_eq!(hash.direction_of(&west), Some(Direction::West));
+
+        let edge = GeoBits::from(&Coord::new(0.0, 179.99), 15);
+        assert_eq!(
+            edge.direction_of(&Coord::new(0.0, -179.0)),
+            Some(Direction::East)
+        );
+    }
+
+    #[test]
+    fn decode_batch() {
+        let cells = [
+            GeoBits::from(&Coord::new(25.006, 121.46), 15),
+            GeoBits::from(&Coord::new(-33.87, 151.21), 32),
+            GeoBits::from(&Coord::new(57.64911, 10.40744), 1),
+        ];
+        let centers = super::decode_batch(&cells);
+        assert_eq!(centers.len(), 3);
+        for (cell, center) in cells.iter().zip(&centers) {
+            assert_eq!(*center, cell.to_coord(Anchor::Center));
+        }
+        assert!(super::decode_batch(&[]).is_empty());
+    }
+
+    #[test]
+    fn rounding_mode() {
+        // Half way through row 2 and column 3 of the precision 3 grid.
+        let coord = Coord::new(-33.75, -22.5);
+        let index = |mode| GeoBits::from_with_rounding(&coord, 3, mode).grid_index();
+        assert_eq!(index(RoundingMode::Truncate), (2, 3));
+        assert_eq!(index(RoundingMode::Nearest), (3, 4));
+        assert_eq!(index(RoundingMode::BankersRounding), (2, 4));
+        assert_eq!(
+            GeoBits::from_with_rounding(&coord, 3, RoundingMode::default()),
+            GeoBits::from(&coord, 3)
+        );
+
+        // The last column does not round past the antimeridian.
+        let east = Coord::new(0.0, 179.9);
+        let (_, lng) = GeoBits::from_with_rounding(&east, 3, RoundingMode::Nearest).grid_index();
+        assert_eq!(lng, 7);
+    }
+
+    #[test]
+    fn transition() {
+        let from = Coord::new(25.006, 121.46);
+        assert_eq!(
+            super::transition(&from, &Coord::new(25.0061, 121.4601), 15),
+            None
+        );
+        let to = Coord::new(25.1, 121.46);
+        assert_eq!(
+            super::transition(&from, &to, 15),
+            Some((GeoBits::from(&from, 15), GeoBits::from(&to, 15)))
+        );
+        assert_eq!(super::transition(&from, &from, 32), None);
+    }
+
+    #[test]
+    fn rollup_error_m() {
+        let coord = Coord::new(25.006, 121.46);
+        let errors: Vec<f32> = [25, 20, 15, 10]
+            .iter()
+            .map(|precision| GeoBits::from(&coord, *precision).rollup_error_m())
+            .collect();
+        assert!(errors
+            .windows(2)
+            .all(|pair| 0.0 < pair[0] && pair[0] < pair[1]));
+        // Doubling a cell's size adds about its own center-to-corner error.
+        let hash = GeoBits::from(&coord, 20);
+        let own = Area::from(hash).diagonal_meters() / 2.0;
+        assert!((hash.rollup_error_m() / own - 1.0).abs() < 0.01);
+        assert_eq!(GeoBits::from(&coord, 1).rollup_error_m(), f32::INFINITY);
+    }
+
+    #[test]
+    fn get_neighbor_spherical() {
+        let top = GeoBits::from(&Coord::new(89.99, 10.0), 10);
+        let over = top.get_neighbor_spherical(Direction::North);
+        let (lat, lng) = top.grid_index();
+        assert_eq!(over.grid_index(), (lat, (lng + 512) % 1024));
+        assert!(over.longitude_range().contains(&-170.0));
+        assert_ne!(over, top.get_neighbor(Direction::North));
+
+        let bottom = GeoBits::from(&Coord::new(-89.99, -170.0), 10);
+        let over = bottom.get_neighbor_spherical(Direction::SouthEast);
+        let (lat, lng) = bottom.grid_index();
+        assert_eq!(over.grid_index(), (lat, lng + 513));
+
+        // Away from the poles it matches get_neighbor.
+        let hash = GeoBits::from(&Coord::new(25.006, 121.46), 10);
+        assert_eq!(
+            hash.get_neighbor_spherical(Direction::North),
+            hash.get_neighbor(Direction::North)
+        );
+        assert_eq!(
+            hash.get_neighbor_spherical(Direction::SouthWest),
+            hash.get_neighbor(Direction::SouthWest)
+        );
+    }
+
+    #[test]
+    fn gray_key() {
+        let mut state = 17;
+        for _ in 0..100 {
+            let precision = (next_random(&mut state) % 32 + 1) as u8;
+            let hash = GeoBits {
+                bits: next_random(&mut state) >> (64 - precision as u32 * 2),
+                precision,
+            };
+            assert_eq!(GeoBits::from_gray_key(hash.gray_key(), precision), Ok(hash));
+        }
+
+        let keys: Vec<u64> = GeoBits::from(&Coord::new(25.006, 121.46), 10)
+            .descendants(14)
+            .map(|cell| cell.gray_key())
+            .collect();
+        assert!(keys
+            .windows(2)
+            .all(|pair| (pair[0] ^ pair[1]).count_ones() == 1));
+        assert_eq!(
+            GeoBits::from_gray_key(0, 33),
+            Err(GeoError::InvalidPrecision(33))
+        );
+    }
+
+    #[test]
+    fn random_coord() {
+        let mut state = 19;
+        let mut uniform = || (next_random(&mut state) >> 11) as f64 / (1u64 << 53) as f64;
+        let coords: Vec<Coord> = (0..10_000)
+            .map(|_| super::random_coord(&mut uniform))
+            .collect();
+        assert!(coords
+            .iter()
+            .all(|coord| Coord::try_from((coord.latitude, coord.longitude)).is_ok()));
+        // Above 60 degrees is 1 - sin(60°) ≈ 13% of the sphere, not the third
+        // of the latitude degrees.
+        let polar = coords
+            .iter()
+            .filter(|coord| coord.latitude.abs() > 60.0)
+            .count();
+        assert!((1_000..1_700).contains(&polar));
+        let east = coords.iter().filter(|coord| coord.longitude > 0.0).count();
+        assert!((4_700..5_300).contains(&east));
+
+        let mut edge = || 1f64.next_down();
+        let coord = super::random_coord(&mut edge);
+        assert!(coord.latitude < 90.0 && coord.longitude < 180.0);
+    }
+
+    #[test]
+    fn neighbor_iter() {
+        let hash = GeoBits::from(&Coord::new(25.006, 121.46), 15);
+        let neighbors: Vec<(Direction, GeoBits)> = hash.neighbor_iter().collect();
+        assert_eq!(neighbors.len(), 8);
+        for (direction, neighbor) in neighbors {
+            assert_eq!(neighbor, hash.get_neighbor(direction));
+        }
+        let cells: Vec<GeoBits> = hash.neighbor_iter().map(|(_, cell)| cell).collect();
+        assert_eq!(cells, hash.neighbors_clockwise());
+
+        let east = hash.get_neighbor(Direction::East);
+        let found = hash.neighbor_iter().find(|(_, cell)| *cell == east);
+        assert_eq!(found, Some((Direction::East, east)));
+    }
+
+    #[test]
+    fn encode_with_area() {
+        let mut state = 23;
+        for _ in 0..100 {
+            let lat = (next_random(&mut state) % 180_000) as f32 / 1000.0 - 90.0;
+            let lng = (next_random(&mut state) % 360_000) as f32 / 1000.0 - 180.0;
+            let precision = (next_random(&mut state) % 32 + 1) as u8;
+            let coord = Coord::new(lat, lng);
+            let (hash, area) = coord.encode_with_area(precision);
+            assert_eq!(hash, GeoBits::from(&coord, precision));
+            assert_eq!(area, Area::from(hash));
+            assert!(area.contains(&coord));
+        }
+    }
+
+    #[test]
+    fn path_to() {
+        let start = GeoBits::from(&Coord::new(25.006, 121.46), 15);
+        let dest = GeoBits::from(&Coord::new(25.1, 121.3), 15);
+        let path = start.path_to(&dest).unwrap();
+        assert_eq!(path.first(), Some(&start));
+        assert_eq!(path.last(), Some(&dest));
+        for pair in path.windows(2) {
+            assert!(pair[0].neighbors_clockwise().contains(&pair[1]));
+        }
+        let (lat, lng) = start.grid_index();
+        let (dest_lat, dest_lng) = dest.grid_index();
+        let steps = lat.abs_diff(dest_lat).max(lng.abs_diff(dest_lng));
+        assert_eq!(path.len(), steps as usize + 1);
+
+        assert_eq!(start.path_to(&start), Some(vec![start]));
+        assert_eq!(
+            start.path_to(&GeoBits::from(&Coord::new(25.1, 121.3), 14)),
+            None
+        );
+
+        // Across the antimeridian rather than around the world.
+        let west = GeoBits::from(&Coord::new(0.0, 179.9), 10);
+        let east = GeoBits::from(&Coord::new(0.0, -179.9), 10);
+        assert_eq!(west.path_to(&east).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn lat_band_cells() {
+        let rows = super::lat_band_cells(-1.0, 1.0, 10);
+        assert!(rows.contains(&511) && rows.contains(&512));
+        assert_eq!(rows.len(), 2 * (1.0f32 / 180.0 * 1024.0).ceil() as usize);
+        assert_eq!(super::lat_band_cells(-90.0, 90.0, 3), 0..8);
+        assert_eq!(super::lat_band_cells(10.0, 10.0, 5).len(), 1);
+        assert!(super::lat_band_cells(10.0, -10.0, 5).is_empty());
+        let row = GeoBits::from(&Coord::new(25.006, 121.46), 10)
+            .grid_index()
+            .0;
+        assert!(super::lat_band_cells(25.0, 25.01, 10).contains(&row));
+    }
+
+    #[test]
+    fn shared_prefix_bits() {
+        let hash = GeoBits::from(&Coord::new(25.006, 121.46), 20);
+        assert_eq!(hash.shared_prefix_bits(&hash), 40);
+        assert_eq!(hash.shared_prefix_bits(&hash.parent().unwrap()), 38);
+
+        let mut state = 31;
+        let mut closer = 0;
+        for _ in 0..1000 {
+            let lat = (next_random(&mut state) % 170_000) as f32 / 1000.0 - 85.0;
+            let lng = (next_random(&mut state) % 360_000) as f32 / 1000.0 - 180.0;
+            let hash = GeoBits::from(&Coord::new(lat, lng), 20);
+            let far = GeoBits::from(&Coord::new(-lat, lng / 2.0), 20);
+            if hash.shared_prefix_bits(&hash.get_neighbor(Direction::North))
+                > hash.shared_prefix_bits(&far)
+            {
+                closer += 1;
+            }
+        }
+        assert!(closer > 900);
+
+        // The Z-order seams: neighbors across the equator share only the
+        // leading longitude bit, and across the prime meridian nothing.
+        let south = GeoBits::from(&Coord::new(-0.0001, 10.0), 20);
+        let north = south.get_neighbor(Direction::North);
+        assert_eq!(north.latitude_range().start, 0.0);
+        assert_eq!(north.shared_prefix_bits(&south), 1);
+        let west = GeoBits::from(&Coord::new(10.0, -0.0001), 20);
+        let east = west.get_neighbor(Direction::East);
+        assert_eq!(east.longitude_range().start, 0.0);
+        assert_eq!(east.shared_prefix_bits(&west), 0);
+    }
+
+    #[test]
+    fn encode_for_error() {
+        for coord in [Coord::new(25.006, 121.46), Coord::new(-70.0, 5.0)] {
+            for max_error_m in [50.0, 1_000.0, 100_000.0] {
+                let hash = coord.encode_for_error(max_error_m);
+                let center = Area::from(hash).center();
+                assert!(coord.haversine(&center) <= max_error_m);
+                let coarser = Area::from(hash.parent().unwrap());
+                assert!(coarser.half_diagonal_meters() > max_error_m);
+            }
+        }
+        assert_eq!(
+            Coord::new(25.006, 121.46).encode_for_error(0.0).precision,
+            32
+        );
+    }
+
+    #[test]
+    fn coarse_neighbors() {
+        let coarse = GeoBits::from(&Coord::new(25.006, 121.46), 10);
+        // The north-east-most descendant four levels down.
+        let corner = coarse.descendants(14).last().unwrap();
+        assert_eq!(
+            corner.coarse_neighbors(10),
+            vec![
+                coarse.get_neighbor(Direction::North),
+                coarse.get_neighbor(Direction::NorthEast),
+                coarse.get_neighbor(Direction::East),
+            ]
+        );
+
+        // A cell on the western edge, away from the corners.
+        let west = coarse.descendants(14).find(|cell| {
+            let (lat, lng) = cell.grid_index();
+            lng % 16 == 0 && lat % 16 == 8
+        });
+        assert_eq!(
+            west.unwrap().coarse_neighbors(10),
+            vec![coarse.get_neighbor(Direction::West)]
+        );
+
+        let inner = coarse.descendants(14).nth(3).unwrap();
+        assert!(inner.coarse_neighbors(10).is_empty());
+        assert_eq!(coarse.coarse_neighbors(10).len(), 8);
+
+        // Nothing lies north of the top row, not even the bottom row.
+        let top = GeoBits::from(&Coord::new(89.99, 10.0), 10);
+        let cells = top.coarse_neighbors(10);
+        assert_eq!(cells.len(), 5);
+        assert!(cells.iter().all(|cell| cell.latitude_range().start > 0.0));
+        let (_, lng) = top.grid_index();
+        let west = GeoBits::from_grid_index((1 << 14) - 1, lng * 16, 14).unwrap();
+        assert_eq!(
+            west.coarse_neighbors(10),
+            vec![top.get_neighbor(Direction::West)]
+        );
     }
 }
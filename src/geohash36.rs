@@ -0,0 +1,98 @@
+use crate::error::GeoError;
+use crate::geohash::{Area, Coord, GeoBits};
+
+/// The Geohash-36 alphabet. Each character picks one cell of a 6x6 grid laid
+/// out row by row from the north-west corner, so it is case-sensitive and
+/// avoids vowels and easily confused characters.
+const ALPHABET: &[u8; 36] = b"23456789bBCdDFgGhHjJKlLMnNPqQrRtTVWX";
+
+impl GeoBits {
+    /// Returns the Geohash-36 string of this cell's center, with enough
+    /// characters that the Geohash-36 cell is no larger than this one.
+    ///
+    /// Geohash-36 divides each level 6x6 instead of 2x2, so its cells never
+    /// line up exactly with ours and the conversion is lossy.
+    pub fn to_geohash36(&self) -> String {
+        let cells = (1u64 << self.precision) as f64;
+        let len = cells.log(6f64).ceil().max(1f64) as usize;
+        encode(&Area::from(*self).center(), len)
+    }
+
+    /// Decodes a Geohash-36 string and returns the cell at `precision`
+    /// containing the center of the Geohash-36 cell.
+    pub fn from_geohash36(s: &str, precision: u8) -> Result<GeoBits, GeoError> {
+        if precision == 0 || precision > 32 {
+            return Err(GeoError::InvalidPrecision(precision));
+        }
+        if s.is_empty() {
+            return Err(GeoError::InvalidLength(0));
+        }
+        let (mut lat_lo, mut lat_hi) = (-90f64, 90f64);
+        let (mut lng_lo, mut lng_hi) = (-180f64, 180f64);
+        for c in s.chars() {
+            let index = ALPHABET
+                .iter()
+                .position(|a| c.is_ascii() && *a == c as u8)
+                .ok_or(GeoError::InvalidCharacter(c))?;
+            let (row, col) = ((index / 6) as f64, (index % 6) as f64);
+            let lat_step = (lat_hi - lat_lo) / 6f64;
+            let lng_step = (lng_hi - lng_lo) / 6f64;
+            lat_hi -= row * lat_step;
+            lat_lo = lat_hi - lat_step;
+            lng_lo += col * lng_step;
+            lng_hi = lng_lo + lng_step;
+        }
+        let center = Coord {
+            latitude: ((lat_lo + lat_hi) / 2f64) as f32,
+            longitude: ((lng_lo + lng_hi) / 2f64) as f32,
+        };
+        Ok(GeoBits::from(&center, precision))
+    }
+}
+
+fn encode(coord: &Coord, len: usize) -> String {
+    let (lat, lng) = (coord.latitude as f64, coord.longitude as f64);
+    let (mut lat_lo, mut lat_hi) = (-90f64, 90f64);
+    let (mut lng_lo, mut lng_hi) = (-180f64, 180f64);
+    let mut encoded = String::with_capacity(len);
+    for _ in 0..len {
+        let lat_step = (lat_hi - lat_lo) / 6f64;
+        let lng_step = (lng_hi - lng_lo) / 6f64;
+        // Rows count down from the north.
+        let row = ((lat_hi - lat) / lat_step).floor().clamp(0f64, 5f64);
+        let col = ((lng - lng_lo) / lng_step).floor().clamp(0f64, 5f64);
+        encoded.push(ALPHABET[row as usize * 6 + col as usize] as char);
+        lat_hi -= row * lat_step;
+        lat_lo = lat_hi - lat_step;
+        lng_lo += col * lng_step;
+        lng_hi = lng_lo + lng_step;
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn geohash36() {
+        // London Bridge.
+        let coord = Coord::new(51.504444, -0.086666);
+        assert_eq!(encode(&coord, 10), "bdrdC26BqH");
+
+        for precision in [1, 10, 16, 25, 32] {
+            let hash = GeoBits::from(&coord, precision);
+            let encoded = hash.to_geohash36();
+            assert_eq!(GeoBits::from_geohash36(&encoded, precision), Ok(hash));
+        }
+
+        assert_eq!(
+            GeoBits::from_geohash36("bdrA", 10),
+            Err(GeoError::InvalidCharacter('A'))
+        );
+        assert_eq!(
+            GeoBits::from_geohash36("", 10),
+            Err(GeoError::InvalidLength(0))
+        );
+    }
+}
@@ -0,0 +1,216 @@
+use std::ops::Range;
+
+use crate::bits::interleave64;
+use crate::geohash::{BoundaryRule, Coord, GeoBits, DEFAULT_PRECISION};
+
+/// How input longitudes are expressed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LongitudeConvention {
+    /// Longitudes in [-180, 180).
+    #[default]
+    Signed,
+    /// Longitudes in [0, 360), as used by some maritime and astronomy data.
+    Unsigned,
+}
+
+/// Encoder that bundles precision and input conventions, configured
+/// builder-style.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GeoHasher {
+    precision: u8,
+    longitude_convention: LongitudeConvention,
+    boundary_rule: BoundaryRule,
+    // `(start, end)` of the y and x axes when hashing something other than
+    // latitude and longitude in degrees.
+    domain: Option<[(f64, f64); 2]>,
+}
+
+impl GeoHasher {
+    pub fn new(precision: u8) -> Self {
+        if precision == 0 || precision > 32 {
+            panic!("Precision should satisfy 1 <= precision <= 32");
+        }
+        GeoHasher {
+            precision,
+            longitude_convention: LongitudeConvention::default(),
+            boundary_rule: BoundaryRule::default(),
+            domain: None,
+        }
+    }
+
+    pub fn longitude_convention(mut self, convention: LongitudeConvention) -> Self {
+        self.longitude_convention = convention;
+        self
+    }
+
+    pub fn boundary_rule(mut self, rule: BoundaryRule) -> Self {
+        self.boundary_rule = rule;
+        self
+    }
+
+    /// Hashes an arbitrary 2D domain instead of the globe, e.g. a game map or
+    /// the unit square. `encode_domain` and `decode` then take and return
+    /// values in the domain's units, with `y` in place of latitude and `x` in
+    /// place of longitude. The longitude convention does not apply to a
+    /// custom domain.
+    pub fn domain(mut self, y: Range<f64>, x: Range<f64>) -> Self {
+        if !(y.start < y.end && x.start < x.end) {
+            panic!("Domain ranges should be non-empty");
+        }
+        self.domain = Some([(y.start, y.end), (x.start, x.end)]);
+        self
+    }
+
+    pub fn encode(&self, latitude: f32, longitude: f32) -> GeoBits {
+        if self.domain.is_some() {
+            return self.encode_domain(latitude as f64, longitude as f64);
+        }
+        let longitude = match self.longitude_convention {
+            LongitudeConvention::Signed => longitude,
+            LongitudeConvention::Unsigned => {
+                if !(0f32..360f32).contains(&longitude) {
+                    panic!("longitude must be in (0, 360).");
+                }
+                if longitude >= 180f32 {
+                    longitude - 360f32
+                } else {
+                    longitude
+                }
+            }
+        };
+        GeoBits::from_with_rule(
+            &Coord::new(latitude, longitude),
+            self.precision,
+            self.boundary_rule,
+        )
+    }
+
+    /// Encodes a point of the custom domain. Takes `f64` since domain units,
+    /// unlike degrees, can need more resolution than `f32` has: an `f32`
+    /// resolves only whole units beyond 2^24. Panics without a domain.
+    pub fn encode_domain(&self, y: f64, x: f64) -> GeoBits {
+        let Some([y_axis, x_axis]) = self.domain else {
+            panic!("encode_domain needs a domain");
+        };
+        GeoBits {
+            bits: interleave64(self.quantize(y, y_axis), self.quantize(x, x_axis)),
+            precision: self.precision,
+        }
+    }
+
+    /// Returns the `(y, x)` ranges `hash` covers, in the domain's units or in
+    /// degrees of latitude and longitude by default.
+    pub fn decode(&self, hash: &GeoBits) -> (Range<f64>, Range<f64>) {
+        let Some([y, x]) = self.domain else {
+            let (lat, lng) = (hash.latitude_range(), hash.longitude_range());
+            return (
+                lat.start as f64..lat.end as f64,
+                lng.start as f64..lng.end as f64,
+            );
+        };
+        let (lat, lng) = hash.grid_index();
+        let scale = (1u64 << hash.precision()) as f64;
+        let range = |index: u32, (start, end): (f64, f64)| {
+            let step = (end - start) / scale;
+            start + index as f64 * step..start + (index + 1) as f64 * step
+        };
+        (range(lat, y), range(lng, x))
+    }
+
+    // Returns the grid index of `value` along an axis spanning `[start, end)`.
+    fn quantize(&self, value: f64, (start, end): (f64, f64)) -> u32 {
+        if !(start..end).contains(&value) {
+            panic!("value must be in ({}, {}).", start, end);
+        }
+        let scale = (1u64 << self.precision) as f64;
+        let scaled = (value - start) * scale / (end - start);
+        let index = match self.boundary_rule {
+            BoundaryRule::TowardNorthEast => scaled.floor(),
+            BoundaryRule::TowardSouthWest => (scaled.ceil() - 1f64).max(0f64),
+        };
+        // Rounding can carry a value just below `end` onto the next row.
+        index.min(scale - 1f64) as u32
+    }
+}
+
+impl Default for GeoHasher {
+    fn default() -> Self {
+        GeoHasher::new(DEFAULT_PRECISION)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unsigned_longitude() {
+        let standard = GeoHasher::new(20);
+        let unsigned = GeoHasher::new(20).longitude_convention(LongitudeConvention::Unsigned);
+        assert_eq!(unsigned.encode(12.5, 200.0), standard.encode(12.5, -160.0));
+        assert_eq!(unsigned.encode(12.5, 20.0), standard.encode(12.5, 20.0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn unsigned_longitude_out_of_range() {
+        GeoHasher::new(20)
+            .longitude_convention(LongitudeConvention::Unsigned)
+            .encode(12.5, -20.0);
+    }
+
+    #[test]
+    fn unit_square_domain() {
+        let hasher = GeoHasher::new(10).domain(0.0..1.0, 0.0..1.0);
+        let hash = hasher.encode(0.25, 0.8);
+        assert_eq!(hash.grid_index(), (256, 819));
+        let (y, x) = hasher.decode(&hash);
+        assert!(y.contains(&0.25) && x.contains(&0.8));
+        assert_eq!(y.end - y.start, 1.0 / 1024.0);
+
+        // Different units, same grid.
+        let map = GeoHasher::new(10).domain(0.0..4096.0, -512.0..512.0);
+        assert_eq!(map.encode(1024.0, 307.2), hash);
+
+        let default = GeoHasher::new(10);
+        let (lat, lng) = default.decode(&default.encode(25.006, 121.46));
+        assert!(lat.contains(&25.006) && lng.contains(&121.46));
+    }
+
+    #[test]
+    fn wide_domain() {
+        // Millimetres along a 100 km axis; f32 would round these together.
+        let hasher = GeoHasher::new(32).domain(0.0..1e8, 0.0..1e8);
+        let a = hasher.encode_domain(50_000_000.1, 0.0);
+        let b = hasher.encode_domain(50_000_000.7, 0.0);
+        assert_ne!(a, b);
+        assert_eq!(
+            hasher.encode(50_000_000.1, 0.0),
+            hasher.encode(50_000_000.7, 0.0)
+        );
+
+        // The value just below the end stays on the last row.
+        let hasher = GeoHasher::new(32).domain(0.0..0.3, 0.0..1.0);
+        let last = hasher.encode_domain(0.3f64.next_down(), 1f64.next_down());
+        assert_eq!(last.grid_index(), (u32::MAX, u32::MAX));
+    }
+
+    #[test]
+    #[should_panic]
+    fn encode_domain_without_domain() {
+        GeoHasher::new(10).encode_domain(0.5, 0.5);
+    }
+
+    #[test]
+    #[should_panic]
+    fn outside_domain() {
+        GeoHasher::new(10)
+            .domain(0.0..1.0, 0.0..1.0)
+            .encode(1.0, 0.5);
+    }
+
+    #[test]
+    fn default() {
+        assert_eq!(GeoHasher::default(), GeoHasher::new(DEFAULT_PRECISION));
+    }
+}
@@ -0,0 +1,315 @@
+use std::io::{self, Read, Write};
+
+use crate::error::GeoError;
+use crate::geohash::GeoBits;
+
+const MAGIC: &[u8; 4] = b"GHIC";
+const VERSION: u8 = 1;
+// Stored in place of the shared precision when cells differ in precision.
+const MIXED_PRECISION: u8 = 0;
+
+/// Writes `cells` in a versioned binary format:
+///
+/// - the magic bytes `GHIC` and a version byte,
+/// - the number of cells as a LEB128 varint,
+/// - the shared precision, or 0 if the precisions differ,
+/// - for each cell, its precision (only if they differ) followed by the
+///   zigzag varint delta from the previous code. The code is `bits` when the
+///   precision is shared and the Morton code otherwise.
+///
+/// The order of `cells` is preserved. Sorted input gives small deltas, which
+/// is what makes the format compact.
+pub fn write_collection<W: Write>(w: &mut W, cells: &[GeoBits]) -> io::Result<()> {
+    w.write_all(MAGIC)?;
+    w.write_all(&[VERSION])?;
+    write_varint(w, cells.len() as u64)?;
+
+    let shared = match cells.first() {
+        Some(first) if cells.iter().all(|cell| cell.precision == first.precision) => {
+            first.precision
+        }
+        _ => MIXED_PRECISION,
+    };
+    w.write_all(&[shared])?;
+
+    let mut previous = 0u64;
+    for cell in cells {
+        let code = if shared == MIXED_PRECISION {
+            w.write_all(&[cell.precision])?;
+            cell.morton_code()
+        } else {
+            cell.bits
+        };
+        write_varint(w, zigzag(code.wrapping_sub(previous) as i64))?;
+        previous = code;
+    }
+    Ok(())
+}
+
+/// Reads a collection written by `write_collection`.
+pub fn read_collection<R: Read>(r: &mut R) -> io::Result<Vec<GeoBits>> {
+    let mut magic = [0u8; 4];
+    r.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(invalid_data("not a geohash collection"));
+    }
+    let version = read_u8(r)?;
+    if version != VERSION {
+        return Err(invalid_data("unsupported collection version"));
+    }
+    let len = read_varint(r)?;
+    let shared = read_u8(r)?;
+    if shared > 32 {
+        return Err(invalid_data("precision out of range"));
+    }
+
+    let mut cells = Vec::new();
+    let mut previous = 0u64;
+    for _ in 0..len {
+        let precision = if shared == MIXED_PRECISION {
+            read_u8(r)?
+        } else {
+            shared
+        };
+        if precision == 0 || precision > 32 {
+            return Err(invalid_data("precision out of range"));
+        }
+        let code = previous.wrapping_add(unzigzag(read_varint(r)?) as u64);
+        previous = code;
+        // Mixed codes are left-aligned and uniform ones right-aligned; either
+        // way, the bits past the precision must be clear.
+        let num_bits = precision as u32 * 2;
+        let (bits, stray) = if shared == MIXED_PRECISION {
+            (code >> (64 - num_bits), code.checked_shl(num_bits))
+        } else {
+            (code, code.checked_shr(num_bits))
+        };
+        if stray.unwrap_or(0) != 0 {
+            return Err(invalid_data("bits set beyond the precision"));
+        }
+        cells.push(GeoBits { bits, precision });
+    }
+    Ok(cells)
+}
+
+/// Writes a single cell as its precision byte followed by the big-endian
+/// Morton code, truncated to the `ceil(precision * 2 / 8)` bytes that hold
+/// significant bits.
+pub fn write_framed<W: Write>(w: &mut W, cell: &GeoBits) -> io::Result<()> {
+    let len = (cell.precision as usize * 2).div_ceil(8);
+    w.write_all(&[cell.precision])?;
+    w.write_all(&cell.morton_code().to_be_bytes()[..len])
+}
+
+/// Reads a single cell written by `write_framed`.
+pub fn read_framed<R: Read>(r: &mut R) -> io::Result<GeoBits> {
+    let precision = read_u8(r)?;
+    if precision == 0 || precision > 32 {
+        return Err(invalid_data("precision out of range"));
+    }
+    let num_bits = precision as u32 * 2;
+    let mut bytes = [0u8; 8];
+    r.read_exact(&mut bytes[..num_bits.div_ceil(8) as usize])?;
+    let code = u64::from_be_bytes(bytes);
+    if num_bits < 64 && code << num_bits != 0 {
+        return Err(invalid_data("bits set beyond the precision"));
+    }
+    Ok(GeoBits {
+        bits: code >> (64 - num_bits),
+        precision,
+    })
+}
+
+/// Returns the sorted, deduplicated 32-bit keys of `cells`, ready to insert
+/// into a bitmap such as a `RoaringBitmap`. The key is the cell's grid index
+/// in row-major order, `lat_idx << precision | lng_idx`, so cells must share a
+/// precision of at most 16 for it to fit in a `u32`.
+pub fn to_bitmap_keys(cells: &[GeoBits]) -> Result<Vec<u32>, GeoError> {
+    let Some(first) = cells.first() else {
+        return Ok(Vec::new());
+    };
+    if let Some(cell) = cells
+        .iter()
+        .find(|cell| cell.precision > 16 || cell.precision != first.precision)
+    {
+        return Err(GeoError::InvalidPrecision(cell.precision));
+    }
+    let mut keys: Vec<u32> = cells
+        .iter()
+        .map(|cell| {
+            let (lat, lng) = cell.grid_index();
+            lat << first.precision | lng
+        })
+        .collect();
+    keys.sort_unstable();
+    keys.dedup();
+    Ok(keys)
+}
+
+/// Rebuilds the cells at `precision` from keys made by `to_bitmap_keys`.
+pub fn from_bitmap_keys<I: IntoIterator<Item = u32>>(
+    keys: I,
+    precision: u8,
+) -> Result<Vec<GeoBits>, GeoError> {
+    if precision == 0 || precision > 16 {
+        return Err(GeoError::InvalidPrecision(precision));
+    }
+    keys.into_iter()
+        .map(|key| {
+            if (key as u64) >> (precision as u32 * 2) != 0 {
+                return Err(GeoError::InvalidGridIndex(key));
+            }
+            let lng_mask = (1 << precision) - 1;
+            GeoBits::from_grid_index(key >> precision, key & lng_mask, precision)
+        })
+        .collect()
+}
+
+fn zigzag(n: i64) -> u64 {
+    ((n << 1) ^ (n >> 63)) as u64
+}
+
+fn unzigzag(n: u64) -> i64 {
+    ((n >> 1) as i64) ^ -((n & 1) as i64)
+}
+
+fn write_varint<W: Write>(w: &mut W, mut n: u64) -> io::Result<()> {
+    while n >= 0x80 {
+        w.write_all(&[(n as u8) | 0x80])?;
+        n >>= 7;
+    }
+    w.write_all(&[n as u8])
+}
+
+fn read_varint<R: Read>(r: &mut R) -> io::Result<u64> {
+    let mut n = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = read_u8(r)?;
+        n |= ((byte & 0x7F) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(n);
+        }
+    }
+    Err(invalid_data("varint too long"))
+}
+
+fn read_u8<R: Read>(r: &mut R) -> io::Result<u8> {
+    let mut byte = [0u8; 1];
+    r.read_exact(&mut byte)?;
+    Ok(byte[0])
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::cover::cover_bbox;
+    use crate::geohash::Coord;
+
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut cells = cover_bbox(&Coord::new(25.0, 121.0), &Coord::new(25.5, 121.5), 12);
+        cells.sort_by_key(|cell| cell.bits);
+        let mut buf = Vec::new();
+        write_collection(&mut buf, &cells).unwrap();
+        // Sorted neighbors mostly take a single byte each.
+        assert!(buf.len() < 10 + cells.len() * 2);
+        assert_eq!(read_collection(&mut buf.as_slice()).unwrap(), cells);
+
+        let mixed = [
+            GeoBits::from(&Coord::new(-33.87, 151.21), 32),
+            GeoBits::from(&Coord::new(25.006, 121.46), 3),
+            GeoBits::from(&Coord::new(25.006, 121.46), 20),
+        ];
+        let mut buf = Vec::new();
+        write_collection(&mut buf, &mixed).unwrap();
+        assert_eq!(read_collection(&mut buf.as_slice()).unwrap(), mixed);
+
+        let mut buf = Vec::new();
+        write_collection(&mut buf, &[]).unwrap();
+        assert_eq!(read_collection(&mut buf.as_slice()).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn framed() {
+        let cells = [
+            GeoBits::from(&Coord::new(25.006, 121.46), 15),
+            GeoBits::from(&Coord::new(-33.87, 151.21), 32),
+            GeoBits::from(&Coord::new(57.64911, 10.40744), 1),
+        ];
+        let mut cursor = Cursor::new(Vec::new());
+        for cell in &cells {
+            write_framed(&mut cursor, cell).unwrap();
+        }
+        assert_eq!(cursor.get_ref().len(), (1 + 4) + (1 + 8) + (1 + 1));
+        cursor.set_position(0);
+        for cell in &cells {
+            assert_eq!(read_framed(&mut cursor).unwrap(), *cell);
+        }
+        let err = read_framed(&mut cursor).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+
+        let err = read_framed(&mut b"\x21\x00".as_slice()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        let err = read_framed(&mut b"\x01\x01".as_slice()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn bitmap_keys() {
+        let mut cells = cover_bbox(&Coord::new(25.0, 121.0), &Coord::new(25.5, 121.5), 12);
+        cells.sort_by_key(|cell| cell.grid_index());
+        let keys = to_bitmap_keys(&cells).unwrap();
+        assert_eq!(keys.len(), cells.len());
+        let (lat, lng) = cells[0].grid_index();
+        assert_eq!(keys[0], lat << 12 | lng);
+        // A dense block is runs of consecutive keys, one run per row.
+        let runs = keys
+            .windows(2)
+            .filter(|pair| pair[1] != pair[0] + 1)
+            .count()
+            + 1;
+        let rows = cells.last().unwrap().grid_index().0 - lat + 1;
+        assert_eq!(runs as u32, rows);
+        assert_eq!(from_bitmap_keys(keys, 12).unwrap(), cells);
+
+        let fine = GeoBits::from(&Coord::new(25.006, 121.46), 17);
+        assert_eq!(to_bitmap_keys(&[fine]), Err(GeoError::InvalidPrecision(17)));
+        let mixed = [cells[0], GeoBits::from(&Coord::new(25.006, 121.46), 10)];
+        assert_eq!(to_bitmap_keys(&mixed), Err(GeoError::InvalidPrecision(10)));
+        assert_eq!(
+            from_bitmap_keys([1 << 10], 5),
+            Err(GeoError::InvalidGridIndex(1 << 10))
+        );
+    }
+
+    #[test]
+    fn invalid_header() {
+        let err = read_collection(&mut b"GHIX\x01\x00\x00".as_slice()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        let err = read_collection(&mut b"GHIC\x02\x00\x00".as_slice()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        // One precision 2 cell: 15 fits in its 4 bits, 16 does not.
+        let cells = read_collection(&mut b"GHIC\x01\x01\x02\x1e".as_slice()).unwrap();
+        assert_eq!(
+            cells,
+            vec![GeoBits {
+                bits: 15,
+                precision: 2
+            }]
+        );
+        let err = read_collection(&mut b"GHIC\x01\x01\x02\x20".as_slice()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        // Mixed precisions store Morton codes, whose low bits must be clear: a
+        // precision 1 cell with code 1.
+        let err = read_collection(&mut b"GHIC\x01\x01\x00\x01\x02".as_slice()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -1,2 +1,12 @@
+pub mod aggregate;
+pub mod anisotropic;
+pub mod base32;
 mod bits;
+pub mod cover;
+pub mod error;
 pub mod geohash;
+pub mod geohash36;
+pub mod hasher;
+pub mod io;
+pub mod s2;
+pub mod tile;
@@ -0,0 +1,103 @@
+use crate::geohash::{Area, GeoBits};
+
+const S2_MAX_LEVEL: u8 = 30;
+
+// Hilbert curve tables from the S2 reference implementation, indexed by the
+// current orientation (swap and invert bits).
+const IJ_TO_POS: [[u64; 4]; 4] = [[0, 1, 3, 2], [0, 3, 1, 2], [2, 3, 1, 0], [2, 1, 3, 0]];
+const POS_TO_ORIENTATION: [usize; 4] = [1, 0, 0, 3];
+
+impl GeoBits {
+    /// Returns the id of the S2 cell containing this cell's center, at S2 level
+    /// `precision - 1` (capped at 30), which matches our latitude extent.
+    ///
+    /// This is only an approximation: S2 projects the sphere onto the six
+    /// faces of a cube, so its cells are neither aligned with nor the same
+    /// shape as our lat/lng cells, and one of our cells may overlap several S2
+    /// cells.
+    pub fn to_s2_approx(&self) -> u64 {
+        let level = (self.precision - 1).min(S2_MAX_LEVEL);
+        let [x, y, z] = Area::from(*self).center().to_unit_vector();
+
+        // Pick the cube face the point projects onto and its (u, v) there.
+        let (face, u, v) = if x.abs() >= y.abs() && x.abs() >= z.abs() {
+            if x >= 0f64 {
+                (0, y / x, z / x)
+            } else {
+                (3, z / x, y / x)
+            }
+        } else if y.abs() >= z.abs() {
+            if y >= 0f64 {
+                (1, -x / y, z / y)
+            } else {
+                (4, z / y, -x / y)
+            }
+        } else if z >= 0f64 {
+            (2, -x / z, -y / z)
+        } else {
+            (5, -y / z, -x / z)
+        };
+
+        let i = st_to_ij(uv_to_st(u));
+        let j = st_to_ij(uv_to_st(v));
+
+        // Walk the Hilbert curve from the face down to the leaf level.
+        let mut orientation = face as usize & 1;
+        let mut pos = 0u64;
+        for k in (0..S2_MAX_LEVEL).rev() {
+            let ij = (((i >> k) & 1) << 1 | ((j >> k) & 1)) as usize;
+            let quadrant = IJ_TO_POS[orientation][ij];
+            pos = (pos << 2) | quadrant;
+            orientation ^= POS_TO_ORIENTATION[quadrant as usize];
+        }
+        let leaf = ((face as u64) << 61) | (pos << 1) | 1;
+
+        // Truncate the leaf id to the requested level.
+        let lsb = 1u64 << (2 * (S2_MAX_LEVEL - level));
+        (leaf & lsb.wrapping_neg()) | lsb
+    }
+}
+
+// S2's quadratic projection, which makes cell areas more uniform than the
+// plain gnomonic projection.
+fn uv_to_st(u: f64) -> f64 {
+    if u >= 0f64 {
+        0.5 * (1f64 + 3f64 * u).sqrt()
+    } else {
+        1f64 - 0.5 * (1f64 - 3f64 * u).sqrt()
+    }
+}
+
+fn st_to_ij(s: f64) -> u32 {
+    let max = (1u64 << S2_MAX_LEVEL) as f64;
+    (s * max).floor().clamp(0f64, max - 1f64) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geohash::{Coord, Direction};
+
+    use super::*;
+
+    #[test]
+    fn to_s2_approx() {
+        let hash = GeoBits::from(&Coord::new(25.006, 121.46), 15);
+        let id = hash.to_s2_approx();
+        // Level 14 ids have their trailing marker bit at position 2 * (30 - 14).
+        assert_eq!(id.trailing_zeros(), 32);
+
+        let east = hash.get_neighbor(Direction::East).to_s2_approx();
+        let north = hash.get_neighbor(Direction::North).to_s2_approx();
+        let far = GeoBits::from(&Coord::new(-33.87, 151.21), 15).to_s2_approx();
+        // Both neighbors are on the same cube face and much closer along the
+        // Hilbert curve than a cell on another continent.
+        assert_eq!(id >> 61, east >> 61);
+        assert_eq!(id >> 61, north >> 61);
+        assert!(id.abs_diff(east) < id.abs_diff(far));
+        assert!(id.abs_diff(north) < id.abs_diff(far));
+
+        // The point (0, 0) lies at the center of face 0.
+        let origin = GeoBits::from(&Coord::new(0.0, 0.0), 32);
+        assert_eq!(origin.to_s2_approx(), 0x1000000000000001);
+    }
+}
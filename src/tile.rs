@@ -0,0 +1,98 @@
+use std::f64::consts::PI;
+
+use crate::geohash::{Area, Coord, GeoBits};
+
+// Web Mercator is cut off at the latitude that makes the world square.
+const MERCATOR_LAT_MAX: f64 = 85.051_128_779_806_59;
+
+impl GeoBits {
+    /// Returns the `(zoom, x, y)` slippy-map tile containing this cell's
+    /// center, at the zoom whose tiles are as wide as this cell.
+    ///
+    /// Tiles are square in Web Mercator while our cells are equirectangular,
+    /// so the two only line up in longitude; a cell may straddle several
+    /// tiles vertically, especially away from the equator. Latitudes beyond
+    /// ±85.05° fall on the top or bottom row.
+    pub fn to_tile(&self) -> (u32, u32, u32) {
+        let zoom = self.precision as u32;
+        let center = Area::from(*self).center();
+        let n = (1u64 << zoom) as f64;
+        let max = (1u64 << zoom) - 1;
+
+        let x = ((center.longitude as f64 + 180f64) / 360f64 * n) as u64;
+        let lat = (center.latitude as f64)
+            .clamp(-MERCATOR_LAT_MAX, MERCATOR_LAT_MAX)
+            .to_radians();
+        let y = (1f64 - lat.tan().asinh() / PI) / 2f64 * n;
+        (
+            zoom,
+            x.min(max) as u32,
+            (y.max(0f64) as u64).min(max) as u32,
+        )
+    }
+
+    /// Returns the cell at `precision` containing the point at Web Mercator
+    /// tile coordinates `(x, y)` at `zoom`, where whole numbers are tile
+    /// corners and `y` grows southward. Divide pixel coordinates by the tile
+    /// size, usually 256, first. Points outside the map are clamped to it.
+    /// `zoom` must be at most 32, as fine as the finest precision.
+    pub fn from_web_mercator(x: f64, y: f64, zoom: u8, precision: u8) -> GeoBits {
+        if zoom > 32 {
+            panic!("Zoom should satisfy zoom <= 32");
+        }
+        let n = (1u64 << zoom) as f64;
+        let longitude = (x / n).clamp(0f64, 1f64) * 360f64 - 180f64;
+        let latitude = (PI * (1f64 - 2f64 * (y / n).clamp(0f64, 1f64)))
+            .sinh()
+            .atan()
+            .to_degrees();
+        let coord = Coord::new(latitude as f32, (longitude as f32).min(180f32.next_down()));
+        GeoBits::from(&coord, precision)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_web_mercator() {
+        // The middle of the map at any zoom is (0, 0).
+        assert_eq!(
+            GeoBits::from_web_mercator(512.0, 512.0, 10, 20),
+            GeoBits::from(&Coord::new(0.0, 0.0), 20)
+        );
+
+        // London, projected forward to fractional tile coordinates at zoom 10.
+        let (lat, lng) = (51.5074f64, -0.1278f64);
+        let n = 1024.0;
+        let x = (lng + 180.0) / 360.0 * n;
+        let y = (1.0 - lat.to_radians().tan().asinh() / PI) / 2.0 * n;
+        assert_eq!((x as u32, y as u32), (511, 340));
+        let hash = GeoBits::from_web_mercator(x, y, 10, 20);
+        assert_eq!(hash, GeoBits::from(&Coord::new(51.5074, -0.1278), 20));
+
+        // The top-left corner of the map.
+        let corner = GeoBits::from_web_mercator(0.0, 0.0, 3, 10);
+        assert_eq!(corner.grid_index().1, 0);
+        assert!(corner.latitude_range().contains(&85.0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn from_web_mercator_zoom_too_deep() {
+        GeoBits::from_web_mercator(0.0, 0.0, 64, 10);
+    }
+
+    #[test]
+    fn to_tile() {
+        // London is in tile 10/511/340.
+        let london = GeoBits::from(&Coord::new(51.5074, -0.1278), 10);
+        assert_eq!(london.to_tile(), (10, 511, 340));
+
+        let world = GeoBits::from(&Coord::new(0.0, 0.0), 1);
+        assert_eq!(world.to_tile(), (1, 1, 0));
+        let pole = GeoBits::from(&Coord::new(89.9, -179.99), 12);
+        assert_eq!(pole.to_tile(), (12, 0, 0));
+    }
+}